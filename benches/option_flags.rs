@@ -0,0 +1,16 @@
+// Decoding of a full bitmask, which any query returning all the options goes through.
+// Run with `cargo bench`.
+use criterion::{black_box, criterion_group, criterion_main, Criterion};
+use watchdog_device::OptionFlags;
+
+fn bench_from_bitmask(c: &mut Criterion) {
+    c.bench_function("OptionFlags::from_bitmask (all bits)", |b| {
+        b.iter(|| OptionFlags::from_bitmask(black_box(0x87ff)))
+    });
+    c.bench_function("OptionFlags::from_bitmask (no bits)", |b| {
+        b.iter(|| OptionFlags::from_bitmask(black_box(0)))
+    });
+}
+
+criterion_group!(benches, bench_from_bitmask);
+criterion_main!(benches);
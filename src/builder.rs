@@ -0,0 +1,242 @@
+//! Watchdog builder.
+//!
+//! This module allows the configuration of a [`Watchdog`] at the moment of its activation.
+//! It is the recommended way to open a watchdog, since the device, the timeout and the pretimeout
+//! can be configured in a single expression.
+use log::{error, info};
+use std::io;
+use std::path::{Path, PathBuf};
+use std::time::Duration;
+use std::ffi::c_int;
+use crate::watchdog_device::{Watchdog, OptionFlags, KeepAliveMethod};
+use crate::error::WatchdogError;
+
+const DEFAULT_PATH: &str = "/dev/watchdog";
+
+/// Builder used to configure and activate a [`Watchdog`].
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{WatchdogBuilder, KeepAliveMethod};
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// let mut wd = WatchdogBuilder::new()
+///     .timeout(Duration::from_secs(30))
+///     .keepalive_method(KeepAliveMethod::Write)
+///     .verify_keepalive(true)
+///     .build()?;
+/// wd.keep_alive()?;
+/// # wd.magic_close()?;
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Default)]
+pub struct WatchdogBuilder{
+    path: Option<PathBuf>,
+    timeout: Option<Duration>,
+    pretimeout: Option<Duration>,
+    keepalive_method: Option<KeepAliveMethod>,
+    verify_keepalive: bool,
+    keepalive_log_every: Option<u64>,
+    max_keepalive_errors: Option<u32>,
+    custom_open_flags: c_int,
+    keepalive_write_bytes: Option<Vec<u8>>,
+    identity_from_sysfs: bool,
+}
+
+impl WatchdogBuilder {
+    /// Creates a builder for the default watchdog ('/dev/watchdog').
+    pub fn new() -> Self{
+        Self::default()
+    }
+
+    /// Selects the watchdog with the given numeric identifier ('/dev/watchdogID').
+    ///
+    /// See [`Watchdog::new_by_id()`](crate::watchdog_device::Watchdog::new_by_id). Overrides any previous [`path()`](Self::path).
+    pub fn id(mut self, id: u8) -> Self{
+        self.path = Some(PathBuf::from(format!("{DEFAULT_PATH}{id}")));
+        self
+    }
+
+    /// Selects the watchdog at the given path.
+    ///
+    /// See [`Watchdog::open_path()`](crate::watchdog_device::Watchdog::open_path). Overrides any previous [`id()`](Self::id).
+    pub fn path<P: AsRef<Path>>(mut self, path: P) -> Self{
+        self.path = Some(path.as_ref().to_path_buf());
+        self
+    }
+
+    /// Sets the timeout upon activation.
+    ///
+    /// The timeout is applied with [`set_timeout_duration()`](crate::watchdog_device::Watchdog::set_timeout_duration),
+    /// so it is rounded up to whole seconds. If the driver does not support [`OptionFlags::SetTimeout`],
+    /// [`build()`](Self::build) fails with `ErrorKind::Unsupported`.
+    pub fn timeout(mut self, timeout: Duration) -> Self{
+        self.timeout = Some(timeout);
+        self
+    }
+
+    /// Sets the pretimeout upon activation.
+    ///
+    /// The pretimeout is applied after the timeout, with 
+    /// [`set_pretimeout_duration()`](crate::watchdog_device::Watchdog::set_pretimeout_duration).
+    /// If the driver does not support [`OptionFlags::PreTimeout`], [`build()`](Self::build) fails with `ErrorKind::Unsupported`.
+    pub fn pretimeout(mut self, pretimeout: Duration) -> Self{
+        self.pretimeout = Some(pretimeout);
+        self
+    }
+
+    /// Selects how [`keep_alive()`](crate::watchdog_device::Watchdog::keep_alive) pings the watchdog.
+    ///
+    /// See [`KeepAliveMethod`] for the available methods. The default is [`KeepAliveMethod::Ioctl`],
+    /// with an automatic fallback to [`KeepAliveMethod::Write`] if the driver does not support the ioctl.
+    /// Once a method is chosen with this, no fallback takes place.
+    pub fn keepalive_method(mut self, method: KeepAliveMethod) -> Self{
+        self.keepalive_method = Some(method);
+        self
+    }
+
+    /// Verifies that the selected keep alive method works, upon activation.
+    ///
+    /// If enabled, [`build()`](Self::build) pings the watchdog once with the selected [`KeepAliveMethod`],
+    /// and fails if the ping returns an error. This way, a device that does not support the chosen method
+    /// is detected right away, instead of at the first iteration of the keep alive loop.
+    /// Before returning the error, the watchdog is released with
+    /// [`magic_close()`](crate::watchdog_device::Watchdog::magic_close) if supported.
+    ///
+    /// Note that the verification ping happens right after the activation,
+    /// so it consumes a small amount of the first timeout window.
+    pub fn verify_keepalive(mut self, verify: bool) -> Self{
+        self.verify_keepalive = verify;
+        self
+    }
+
+    /// Logs only every Nth successful keep alive ping.
+    ///
+    /// By default, [`keep_alive()`](crate::watchdog_device::Watchdog::keep_alive) logs every successful ping at trace level,
+    /// which can flood the logs of long-running daemons. With this option, only every Nth ping is logged.
+    /// Setting it to 0 disables the logging of successful pings entirely.
+    pub fn keepalive_log_every(mut self, n: u64) -> Self{
+        self.keepalive_log_every = Some(n);
+        self
+    }
+
+    /// Sets the number of consecutive keep alive errors after which the automatic keep alive thread gives up.
+    ///
+    /// See [`Watchdog::set_max_keepalive_errors()`](crate::watchdog_device::Watchdog::set_max_keepalive_errors).
+    pub fn max_keepalive_errors(mut self, max_errors: u32) -> Self{
+        self.max_keepalive_errors = Some(max_errors);
+        self
+    }
+
+    /// Adds custom flags to the ones used to open the watchdog device.
+    ///
+    /// The flags (e.g. `libc::O_CLOEXEC`, `libc::O_NONBLOCK`, `libc::O_NOCTTY`) are OR-ed into the default ones, 
+    /// which only request write access. See `std::os::unix::fs::OpenOptionsExt::custom_flags()` for more information.
+    pub fn custom_open_flags(mut self, flags: c_int) -> Self{
+        self.custom_open_flags = flags;
+        self
+    }
+
+    /// Sets the bytes written to the device by the write-based keep alive (see [`KeepAliveMethod::Write`]).
+    ///
+    /// Any byte other than 'V' pings the watchdog, so the default is a single '1'.
+    /// A few non-standard drivers are documented to require a specific feed token instead: this allows to send it.
+    /// The sequence must not be empty nor contain 'V', which would arm the magic close:
+    /// otherwise [`build()`](Self::build) fails with `ErrorKind::InvalidInput`, before activating the watchdog.
+    pub fn keepalive_write_bytes(mut self, bytes: &[u8]) -> Self{
+        self.keepalive_write_bytes = Some(bytes.to_vec());
+        self
+    }
+
+    /// Reads the driver identity from sysfs, instead of issuing the `WDIOC_GETSUPPORT` ioctl.
+    ///
+    /// Useful for drivers where the ioctl is unreliable, while sysfs works. The sysfs directory is resolved
+    /// through the device number, so it is found even if the device node was renamed.
+    /// If the identity is not available from sysfs, the ioctl is used anyway.
+    /// See [`Watchdog::get_driver_identity()`](crate::watchdog_device::Watchdog::get_driver_identity).
+    pub fn identity_from_sysfs(mut self, enable: bool) -> Self{
+        self.identity_from_sysfs = enable;
+        self
+    }
+
+    /// Activates the watchdog with the configured settings.
+    ///
+    /// The timeout and the pretimeout are applied only if set. If applying them fails,
+    /// the watchdog is released with [`magic_close()`](crate::watchdog_device::Watchdog::magic_close) 
+    /// if supported, before returning the error.
+    /// See [`Watchdog::new()`](crate::watchdog_device::Watchdog::new) for more information.
+    pub fn build(self) -> Result<Watchdog, WatchdogError>{
+        if let Some(bytes) = &self.keepalive_write_bytes{
+            if bytes.is_empty() || bytes.contains(&b'V'){
+                return Err(io::Error::new(io::ErrorKind::InvalidInput, 
+                                          "The keep alive bytes must not be empty nor contain the magic close character 'V'").into());
+            }
+        }
+        let path = self.path.as_deref().unwrap_or(Path::new(DEFAULT_PATH));
+        let mut wd = Watchdog::open_instance_with_flags(path, self.custom_open_flags)?;
+        if let Some(method) = self.keepalive_method{
+            wd.keepalive_method = method;
+            wd.keepalive_fallback = false;
+        }
+        if let Some(bytes) = self.keepalive_write_bytes{
+            wd.keepalive_write_bytes = bytes;
+        }
+        wd.identity_from_sysfs = self.identity_from_sysfs;
+        if let Some(n) = self.keepalive_log_every{
+            wd.keepalive_log_every = n;
+        }
+        if let Some(max_errors) = self.max_keepalive_errors{
+            wd.set_max_keepalive_errors(max_errors);
+        }
+        if let Err(e) = apply_timeouts(&wd, self.timeout, self.pretimeout){
+            error!("Configuration of {} failed:{}", path.display(), e);
+            release(&mut wd)?;
+            return Err(e);
+        }
+        if self.verify_keepalive{
+            if let Err(e) = wd.keep_alive(){
+                error!("Keep alive verification with method {} failed:{}", wd.keepalive_method, e);
+                release(&mut wd)?;
+                return Err(e);
+            }
+            info!("Keep alive method {} verified.", wd.keepalive_method);
+        }
+        Ok(wd)
+    }
+}
+
+/// Applies the timeout and the pretimeout, if set.
+fn apply_timeouts(wd: &Watchdog, timeout: Option<Duration>, pretimeout: Option<Duration>) -> Result<(), WatchdogError>{
+    if let Some(timeout) = timeout{
+        require_option(wd, OptionFlags::SetTimeout)?;
+        let applied = wd.set_timeout_duration(timeout)?;
+        info!("Timeout set to {:?} (requested {:?}).", applied, timeout);
+    }
+    if let Some(pretimeout) = pretimeout{
+        require_option(wd, OptionFlags::PreTimeout)?;
+        let applied = wd.set_pretimeout_duration(pretimeout)?;
+        info!("Pretimeout set to {:?} (requested {:?}).", applied, pretimeout);
+    }
+    Ok(())
+}
+
+/// Fails with `ErrorKind::Unsupported` if the watchdog does not support the given option.
+fn require_option(wd: &Watchdog, option: OptionFlags) -> Result<(), WatchdogError>{
+    if wd.is_option_supported(&option)?{
+        Ok(())
+    }
+    else{
+        Err(io::Error::new(io::ErrorKind::Unsupported, format!("The watchdog does not support the option '{option}'")).into())
+    }
+}
+
+/// Releases a watchdog that failed to be configured, if 'Magic Close' is supported.
+fn release(wd: &mut Watchdog) -> Result<(), WatchdogError>{
+    if wd.is_option_supported(&OptionFlags::MagicClose).unwrap_or(false){
+        wd.magic_close()?;
+    }
+    Ok(())
+}
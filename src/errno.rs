@@ -0,0 +1,74 @@
+//! Errno facilities.
+//!
+//! On Unix platforms, the errno values are the ones of `nix::errno::Errno`.
+//! Elsewhere nix is not available, so a stand-in with the values used by the crate takes its place,
+//! in order for the API (e.g. [`WatchdogError::Errno`](crate::error::WatchdogError::Errno)) to be the same on every platform.
+#[cfg(unix)]
+pub use nix::errno::Errno;
+#[cfg(not(unix))]
+pub use stub::Errno;
+
+#[cfg(not(unix))]
+mod stub {
+    use std::error::Error;
+    use std::fmt;
+    use std::io;
+
+    /// Errno values used by the crate, with the same numbers as on Linux.
+    ///
+    /// This mirrors the subset of `nix::errno::Errno` needed on the platforms without nix.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+    #[repr(i32)]
+    pub enum Errno {
+        UnknownErrno = 0,
+        EPERM = 1,
+        ENOENT = 2,
+        EIO = 5,
+        ENXIO = 6,
+        EBADF = 9,
+        EACCES = 13,
+        EFAULT = 14,
+        EBUSY = 16,
+        ENODEV = 19,
+        EINVAL = 22,
+        ENOTTY = 25,
+        ENOSPC = 28,
+        ERANGE = 34,
+        ENOSYS = 38,
+        EOPNOTSUPP = 95,
+    }
+
+    const ALL_ERRNOS: [Errno; 16] = [
+        Errno::UnknownErrno, Errno::EPERM, Errno::ENOENT, Errno::EIO, Errno::ENXIO, Errno::EBADF, Errno::EACCES, Errno::EFAULT,
+        Errno::EBUSY, Errno::ENODEV, Errno::EINVAL, Errno::ENOTTY, Errno::ENOSPC, Errno::ERANGE, Errno::ENOSYS, Errno::EOPNOTSUPP,
+    ];
+
+    impl Errno {
+        /// Returns the errno with the given number, or `UnknownErrno`.
+        pub fn from_i32(e: i32) -> Errno {
+            ALL_ERRNOS.into_iter().find(|errno| *errno as i32 == e).unwrap_or(Errno::UnknownErrno)
+        }
+    }
+
+    impl fmt::Display for Errno {
+        fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "{:?}", self)
+        }
+    }
+
+    impl Error for Errno {}
+
+    /// The numbers are not OS errors on these platforms, so the errno is kept as the payload instead.
+    impl From<Errno> for io::Error {
+        fn from(e: Errno) -> Self {
+            let kind = match e {
+                Errno::ENOSYS | Errno::ENOTTY | Errno::EOPNOTSUPP => io::ErrorKind::Unsupported,
+                Errno::EINVAL | Errno::ERANGE => io::ErrorKind::InvalidInput,
+                Errno::EPERM | Errno::EACCES => io::ErrorKind::PermissionDenied,
+                Errno::ENOENT => io::ErrorKind::NotFound,
+                _ => io::ErrorKind::Other,
+            };
+            io::Error::new(kind, e)
+        }
+    }
+}
@@ -0,0 +1,181 @@
+//! Error facilities.
+//!
+//! The watchdog driver reports failures with plain errno values, whose meaning depends on the context.
+//! This module helps translating them into watchdog-specific terms, and provides [`WatchdogError`],
+//! the error type returned by all the operations of the crate.
+use std::error::Error;
+use std::fmt;
+use std::io;
+use crate::errno::Errno;
+
+/// Errors of the watchdog operations.
+///
+/// It wraps both the I/O errors (e.g. when opening the device) and the errno values returned by the ioctls.
+/// It can be converted from both [`io::Error`] and [`Errno`], and into an [`io::Error`],
+/// so that it can be propagated with `?` in functions returning any of them.
+/// The conversions never lose the errno: see [`raw_os_error()`](Self::raw_os_error).
+///
+/// The errno values most commonly returned by the watchdog drivers are mapped to named variants
+/// ([`NotSupported`](Self::NotSupported), [`Busy`](Self::Busy), [`InvalidArgument`](Self::InvalidArgument)),
+/// whatever their origin (an ioctl, or an I/O operation such as opening the device).
+/// An [`io::Error`] carrying a custom payload is kept as is, in the [`Io`](Self::Io) variant.
+#[derive(Debug)]
+pub enum WatchdogError{
+    /// I/O error, e.g. opening the device or reading sysfs.
+    Io(io::Error),
+    /// Error returned by a watchdog ioctl, other than the ones with a named variant.
+    Errno(Errno),
+    /// The driver does not support the operation (ENOTTY or EOPNOTSUPP): wrong device, or feature not implemented.
+    NotSupported(Errno),
+    /// The watchdog is already in use (EBUSY), e.g. opened by another process.
+    Busy,
+    /// The driver rejected the requested value (EINVAL), e.g. a timeout out of its range.
+    InvalidArgument,
+    /// The mutex protecting a shared watchdog is poisoned, since a thread panicked while holding it.
+    Poisoned,
+    /// The driver applied a different value than the requested one, due to the limitations of the hardware.
+    Clamped{
+        /// Requested value (in seconds).
+        requested: i32,
+        /// Value applied by the driver (in seconds).
+        applied: i32,
+    },
+    /// A write-based keep alive wrote no bytes, so the watchdog was not pinged.
+    ZeroWrite,
+    /// The driver rejected a timeout change, since the watchdog has 'nowayout' set and is already armed.
+    TimeoutLocked{
+        /// Requested timeout (in seconds).
+        requested: i32,
+        /// Error returned by the driver.
+        errno: Errno,
+    },
+    /// The requested pretimeout is not shorter than the timeout, so it would never fire before the reset.
+    PretimeoutTooLong{
+        /// Requested pretimeout (in seconds).
+        pretimeout: i32,
+        /// Configured timeout (in seconds).
+        timeout: i32,
+    },
+}
+
+impl WatchdogError {
+    /// Returns the errno behind the error, if any.
+    ///
+    /// As [`io::Error::raw_os_error()`], this allows branching on specific errno values. 
+    /// The errno is preserved through all the conversions: converting the error into an [`io::Error`] 
+    /// returns an error with the same `raw_os_error()`.
+    pub fn raw_os_error(&self) -> Option<i32> {
+        match self {
+            Self::Io(e) => e.raw_os_error(),
+            Self::Errno(e) | Self::NotSupported(e) | Self::TimeoutLocked{errno: e, ..} => Some(*e as i32),
+            Self::Busy => Some(Errno::EBUSY as i32),
+            Self::InvalidArgument => Some(Errno::EINVAL as i32),
+            Self::Poisoned | Self::Clamped{..} | Self::ZeroWrite | Self::PretimeoutTooLong{..} => None,
+        }
+    }
+
+    /// Returns the errno behind the error, if any, as an [`Errno`].
+    ///
+    /// See [`raw_os_error()`](Self::raw_os_error).
+    pub fn errno(&self) -> Option<Errno> {
+        self.raw_os_error().map(Errno::from_i32)
+    }
+}
+
+impl fmt::Display for WatchdogError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Io(e) => write!(f, "{}", e),
+            Self::Errno(e) | Self::NotSupported(e) => write!(f, "{}: {}", e, explain_error(*e)),
+            Self::Busy => write!(f, "{}: {}", Errno::EBUSY, explain_error(Errno::EBUSY)),
+            Self::InvalidArgument => write!(f, "{}: {}", Errno::EINVAL, explain_error(Errno::EINVAL)),
+            Self::Poisoned => write!(f, "the watchdog mutex is poisoned"),
+            Self::Clamped{requested, applied} => write!(f, "requested {} secs, but the driver applied {} secs", requested, applied),
+            Self::ZeroWrite => write!(f, "no bytes were written to the watchdog: it was not pinged"),
+            Self::TimeoutLocked{requested, errno} => write!(f, "the timeout can't be changed to {} secs ({}): \
+                the watchdog has nowayout set and is already armed, so the timeout must be configured before arming it", requested, errno),
+            Self::PretimeoutTooLong{pretimeout, timeout} => write!(f, "the pretimeout of {} secs must be shorter than the timeout of {} secs, \
+                since it fires that many seconds before the reset", pretimeout, timeout),
+        }
+    }
+}
+
+impl Error for WatchdogError {
+    fn source(&self) -> Option<&(dyn Error + 'static)> {
+        match self {
+            Self::Io(e) => Some(e),
+            Self::Errno(e) | Self::NotSupported(e) | Self::TimeoutLocked{errno: e, ..} => Some(e),
+            Self::Busy => Some(&Errno::EBUSY),
+            Self::InvalidArgument => Some(&Errno::EINVAL),
+            Self::Poisoned | Self::Clamped{..} | Self::ZeroWrite | Self::PretimeoutTooLong{..} => None,
+        }
+    }
+}
+
+impl From<io::Error> for WatchdogError {
+    fn from(e: io::Error) -> Self {
+        // Only the bare OS errors are remapped, so that an error carrying its own payload keeps its message.
+        match e.raw_os_error().map(Errno::from_i32) {
+            Some(errno @ (Errno::ENOTTY | Errno::EOPNOTSUPP | Errno::EBUSY | Errno::EINVAL)) if e.get_ref().is_none() => errno.into(),
+            _ => Self::Io(e),
+        }
+    }
+}
+
+impl From<Errno> for WatchdogError {
+    fn from(e: Errno) -> Self {
+        match e {
+            Errno::ENOTTY | Errno::EOPNOTSUPP => Self::NotSupported(e),
+            Errno::EBUSY => Self::Busy,
+            Errno::EINVAL => Self::InvalidArgument,
+            _ => Self::Errno(e),
+        }
+    }
+}
+
+impl From<WatchdogError> for io::Error {
+    fn from(e: WatchdogError) -> Self {
+        match e {
+            WatchdogError::Io(e) => e,
+            WatchdogError::Errno(e) | WatchdogError::NotSupported(e) => e.into(),
+            WatchdogError::Busy => Errno::EBUSY.into(),
+            WatchdogError::InvalidArgument => Errno::EINVAL.into(),
+            WatchdogError::Poisoned => io::Error::other(e.to_string()),
+            WatchdogError::Clamped{..} | WatchdogError::PretimeoutTooLong{..} => io::Error::new(io::ErrorKind::InvalidInput, e.to_string()),
+            WatchdogError::ZeroWrite => io::Error::new(io::ErrorKind::WriteZero, e.to_string()),
+            // The errno is preserved, at the cost of the explanation.
+            WatchdogError::TimeoutLocked{errno, ..} => errno.into(),
+        }
+    }
+}
+
+/// Explains what an errno means when returned by a watchdog operation.
+///
+/// The explanations are based on how the Linux Kernel watchdog core and drivers use each errno.
+/// Unknown values return a generic explanation.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::explain_error;
+/// use nix::errno::Errno;
+///
+/// println!("{}", explain_error(Errno::ENOTTY));
+/// ```
+pub fn explain_error(e: Errno) -> &'static str {
+    match e {
+        Errno::ENOTTY => "the device does not support this watchdog ioctl: wrong device, or feature not implemented by the driver",
+        Errno::EOPNOTSUPP => "the operation is not supported by the watchdog driver",
+        Errno::EINVAL => "invalid argument: the requested value (e.g. timeout or pretimeout) is out of the range accepted by the driver",
+        Errno::EBUSY => "the watchdog is already in use: only one process at a time can open it, \
+            also through another node of the same watchdog (e.g. '/dev/watchdog' and '/dev/watchdog0')",
+        Errno::EACCES | Errno::EPERM => "permission denied: read/write access to the watchdog device is needed",
+        Errno::ENOENT => "the watchdog device does not exist: check the path, or load a driver (e.g. softdog)",
+        Errno::ENODEV | Errno::ENXIO => "the watchdog device is not available: the driver may have been unloaded",
+        Errno::EBADF => "invalid file descriptor: the watchdog is not open",
+        Errno::EFAULT => "bad address passed to the driver",
+        Errno::EIO => "I/O error while communicating with the watchdog hardware",
+        Errno::ENOSYS => "the watchdog API is not available on this platform: only Linux is supported",
+        _ => "unexpected error from the watchdog driver",
+    }
+}
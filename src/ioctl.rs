@@ -3,10 +3,18 @@
 //! This module regroups the facilities to access the driver via Linux syscalls.
 //! All the ioctl request codes have been obtained from the Linux Kernel headers: 
 //!  - include/uapi/linux/watchdog.h
+//!
 //! Kernel documentation: /Documentation/userspace-api/ioctl/ioctl-number.rst
-use libc::c_int;
-#[cfg(unix)]
+//!
+//! On other platforms the Linux watchdog API does not exist: every ioctl is replaced by a stub failing with `ENOSYS`,
+//! so that the crate still compiles (e.g. for cross-platform applications only using it on Linux).
+// The request codes are only used on Linux.
+#![cfg_attr(not(target_os = "linux"), allow(dead_code))]
+use std::ffi::c_int;
+#[cfg(target_os = "linux")]
 use nix::{ioctl_read, ioctl_readwrite};
+#[cfg(not(target_os = "linux"))]
+use crate::errno::Errno;
 
 
 const WATCHDOG_IOCTL_BASE: u8 = b'W';
@@ -35,6 +43,7 @@ const IDENTITY_STR_LEN: usize = 32;
 /// };
 /// ```
 #[repr(C)] // see https://docs.rust-embedded.org/book/c-tips/index.html#packed-and-aligned-types
+#[derive(Clone, Copy)]
 pub struct watchdog_info{
     /// Flags describing what the device supports
     pub options: u32,           
@@ -54,14 +63,67 @@ impl watchdog_info {
     }
 }
 
-ioctl_read!(ioctl_get_support, WATCHDOG_IOCTL_BASE, WDIOC_GETSUPPORT, watchdog_info);
-ioctl_read!(ioctl_get_status, WATCHDOG_IOCTL_BASE, WDIOC_GETSTATUS, c_int);
-ioctl_read!(ioctl_get_bootstatus, WATCHDOG_IOCTL_BASE, WDIOC_GETBOOTSTATUS, c_int);
-ioctl_read!(ioctl_get_temp, WATCHDOG_IOCTL_BASE, WDIOC_GETTEMP, c_int);
-ioctl_read!(ioctl_set_options, WATCHDOG_IOCTL_BASE, WDIOC_SETOPTIONS, c_int);
-ioctl_read!(ioctl_keepalive, WATCHDOG_IOCTL_BASE, WDIOC_KEEPALIVE, c_int);
-ioctl_readwrite!(ioctl_set_timeout, WATCHDOG_IOCTL_BASE, WDIOC_SETTIMEOUT, c_int);
-ioctl_read!(ioctl_get_timeout, WATCHDOG_IOCTL_BASE, WDIOC_GETTIMEOUT, c_int);
-ioctl_readwrite!(ioctl_set_pretimeout, WATCHDOG_IOCTL_BASE, WDIOC_SETPRETIMEOUT, c_int);
-ioctl_read!(ioctl_get_pretimeout, WATCHDOG_IOCTL_BASE, WDIOC_GETPRETIMEOUT, c_int);
-ioctl_read!(ioctl_get_time_left, WATCHDOG_IOCTL_BASE, WDIOC_GETTIMELEFT, c_int);
+/// Defines a stub with the same signature of the functions generated by the nix ioctl macros, failing with `ENOSYS`.
+#[cfg(not(target_os = "linux"))]
+macro_rules! ioctl_stub {
+    ($name:ident, $ty:ty) => {
+        pub unsafe fn $name(_fd: c_int, _data: *mut $ty) -> Result<c_int, Errno> {
+            Err(Errno::ENOSYS)
+        }
+    };
+}
+
+#[cfg(target_os = "linux")]
+mod linux {
+    use super::*;
+
+    ioctl_read!(ioctl_get_support, WATCHDOG_IOCTL_BASE, WDIOC_GETSUPPORT, watchdog_info);
+    ioctl_read!(ioctl_get_status, WATCHDOG_IOCTL_BASE, WDIOC_GETSTATUS, c_int);
+    ioctl_read!(ioctl_get_bootstatus, WATCHDOG_IOCTL_BASE, WDIOC_GETBOOTSTATUS, c_int);
+    ioctl_read!(ioctl_get_temp, WATCHDOG_IOCTL_BASE, WDIOC_GETTEMP, c_int);
+    ioctl_read!(ioctl_set_options, WATCHDOG_IOCTL_BASE, WDIOC_SETOPTIONS, c_int);
+    ioctl_read!(ioctl_keepalive, WATCHDOG_IOCTL_BASE, WDIOC_KEEPALIVE, c_int);
+    ioctl_readwrite!(ioctl_set_timeout, WATCHDOG_IOCTL_BASE, WDIOC_SETTIMEOUT, c_int);
+    ioctl_read!(ioctl_get_timeout, WATCHDOG_IOCTL_BASE, WDIOC_GETTIMEOUT, c_int);
+    ioctl_readwrite!(ioctl_set_pretimeout, WATCHDOG_IOCTL_BASE, WDIOC_SETPRETIMEOUT, c_int);
+    ioctl_read!(ioctl_get_pretimeout, WATCHDOG_IOCTL_BASE, WDIOC_GETPRETIMEOUT, c_int);
+    ioctl_read!(ioctl_get_time_left, WATCHDOG_IOCTL_BASE, WDIOC_GETTIMELEFT, c_int);
+}
+#[cfg(target_os = "linux")]
+pub use linux::*;
+
+#[cfg(not(target_os = "linux"))]
+mod stubs {
+    use super::*;
+
+    ioctl_stub!(ioctl_get_support, watchdog_info);
+    ioctl_stub!(ioctl_get_status, c_int);
+    ioctl_stub!(ioctl_get_bootstatus, c_int);
+    ioctl_stub!(ioctl_get_temp, c_int);
+    ioctl_stub!(ioctl_set_options, c_int);
+    ioctl_stub!(ioctl_keepalive, c_int);
+    ioctl_stub!(ioctl_set_timeout, c_int);
+    ioctl_stub!(ioctl_get_timeout, c_int);
+    ioctl_stub!(ioctl_set_pretimeout, c_int);
+    ioctl_stub!(ioctl_get_pretimeout, c_int);
+    ioctl_stub!(ioctl_get_time_left, c_int);
+}
+#[cfg(not(target_os = "linux"))]
+pub use stubs::*;
+
+/// File descriptors only exist on Unix: elsewhere every file has an invalid one, which only reaches the stubs.
+#[cfg(not(unix))]
+pub type RawFd = c_int;
+
+/// Stand-in for `std::os::fd::AsRawFd` on the platforms without file descriptors.
+#[cfg(not(unix))]
+pub trait AsRawFd {
+    fn as_raw_fd(&self) -> RawFd;
+}
+
+#[cfg(not(unix))]
+impl AsRawFd for std::fs::File {
+    fn as_raw_fd(&self) -> RawFd {
+        -1
+    }
+}
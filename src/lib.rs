@@ -1,5 +1,29 @@
 mod ioctl;
+mod errno;
 pub mod watchdog_device;
+pub mod builder;
+pub mod error;
+pub mod sysfs;
+pub mod shared;
+pub mod timer;
+pub mod monitor;
+#[cfg(feature = "systemd")]
+mod systemd;
+#[cfg(feature = "test-util")]
+pub mod mock;
 
 // Bringing elements into scope
-pub use crate::watchdog_device::{Watchdog, OptionFlags, SetOptionFlags};
+pub use crate::watchdog_device::{Watchdog, OptionFlags, OptionSet, SetOptionFlags, KeepAliveMethod, KeepAlive, KeepAliveTicker, PretimeoutGovernor, WatchdogInfo, KeepAliveExit, OnDrop, ParseOptionFlagsError, SetTimeoutOutcome, list_devices, resolve_device, set_lifecycle_log_level};
+pub use crate::builder::WatchdogBuilder;
+pub use crate::error::{explain_error, WatchdogError};
+pub use crate::errno::Errno;
+pub use crate::sysfs::{inspect, StaticInfo, SysfsWatchdog};
+pub use crate::shared::{KeepAliveGuard, SharedWatchdog, StatusReader, WatchdogWorker, WorkerStats};
+pub use crate::timer::{WatchdogTimer, keep_alive_with_timer};
+pub use crate::monitor::WatchdogMonitor;
+#[cfg(feature = "tokio")]
+pub use crate::timer::{TokioTimer, TokioKeepAlive, spawn_keep_alive};
+#[cfg(feature = "systemd")]
+pub use crate::systemd::systemd_watchdog_interval;
+#[cfg(feature = "test-util")]
+pub use crate::mock::MockWatchdog;
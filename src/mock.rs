@@ -0,0 +1,153 @@
+//! Testing facilities.
+//!
+//! The integration tests of this crate need an actual watchdog, which is rarely available in CI.
+//! This module provides [`MockWatchdog`], which implements [`KeepAlive`] without any hardware,
+//! so that crates depending on this one can test their watchdog-feeding code anywhere.
+//!
+//! Requires the `test-util` feature.
+use log::trace;
+use std::collections::VecDeque;
+use std::time::{Duration, Instant};
+use crate::errno::Errno;
+use crate::watchdog_device::KeepAlive;
+use crate::error::WatchdogError;
+
+/// Watchdog that records the calls, instead of using any hardware.
+///
+/// Errors can be injected with [`fail_next_keep_alives()`](Self::fail_next_keep_alives) and
+/// [`fail_next_set_timeout()`](Self::fail_next_set_timeout), while [`has_expired()`](Self::has_expired)
+/// tells if a real watchdog would have reset the system by now.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{Errno, KeepAlive, MockWatchdog, WatchdogError};
+///
+/// fn feed<W: KeepAlive>(wd: &mut W) -> Result<(), WatchdogError> {
+///     wd.set_timeout(10)?;
+///     wd.keep_alive()
+/// }
+///
+/// let mut wd = MockWatchdog::new(60);
+/// feed(&mut wd).unwrap();
+/// assert_eq!(wd.keep_alive_count(), 1);
+/// assert_eq!(wd.set_timeout_calls(), &[10]);
+///
+/// wd.fail_next_keep_alives(1, Errno::EIO);
+/// assert_eq!(feed(&mut wd).unwrap_err().errno(), Some(Errno::EIO));
+/// ```
+#[derive(Debug)]
+pub struct MockWatchdog{
+    timeout: i32,
+    last_keep_alive: Instant,
+    keep_alive_count: usize,
+    keep_alive_errors: VecDeque<Errno>,
+    set_timeout_calls: Vec<i32>,
+    set_timeout_error: Option<Errno>,
+    magic_close_count: usize,
+    expired: bool,
+    /// Whether the watchdog was magic closed, and not pinged since.
+    closed: bool,
+}
+
+impl MockWatchdog {
+    /// Creates a mock watchdog with the given timeout (in seconds), activated right away.
+    pub fn new(timeout: i32) -> Self{
+        Self{
+            timeout,
+            last_keep_alive: Instant::now(),
+            keep_alive_count: 0,
+            keep_alive_errors: VecDeque::new(),
+            set_timeout_calls: Vec::new(),
+            set_timeout_error: None,
+            magic_close_count: 0,
+            expired: false,
+            closed: false,
+        }
+    }
+
+    /// Makes the next `count` calls to [`keep_alive()`](KeepAlive::keep_alive) fail with the given errno.
+    pub fn fail_next_keep_alives(&mut self, count: usize, errno: Errno){
+        self.keep_alive_errors.extend(std::iter::repeat(errno).take(count));
+    }
+
+    /// Makes the next call to [`set_timeout()`](KeepAlive::set_timeout) fail with the given errno.
+    pub fn fail_next_set_timeout(&mut self, errno: Errno){
+        self.set_timeout_error = Some(errno);
+    }
+
+    /// Simulates the expiration of the timeout, as if the watchdog was not pinged in time.
+    pub fn expire(&mut self){
+        self.expired = true;
+    }
+
+    /// Tells if a real watchdog would have triggered a reset.
+    ///
+    /// This happens if the timeout elapsed since the last successful ping (or the activation),
+    /// or if [`expire()`](Self::expire) was called. A magic close prevents the expiration until the next ping,
+    /// which re-arms the watchdog as writing to the device does. An expiration is final: neither a ping
+    /// nor a magic close can undo a reset that already happened.
+    pub fn has_expired(&self) -> bool{
+        self.expired || (!self.closed && self.timeout_elapsed())
+    }
+
+    /// Tells if the timeout elapsed since the last successful ping (or the activation).
+    fn timeout_elapsed(&self) -> bool{
+        self.last_keep_alive.elapsed() >= Duration::from_secs(self.timeout.max(0) as u64)
+    }
+
+    /// Returns the number of successful keep alive pings.
+    pub fn keep_alive_count(&self) -> usize{
+        self.keep_alive_count
+    }
+
+    /// Returns the timeouts requested with [`set_timeout()`](KeepAlive::set_timeout), in order.
+    pub fn set_timeout_calls(&self) -> &[i32]{
+        &self.set_timeout_calls
+    }
+
+    /// Returns the number of calls to [`magic_close()`](KeepAlive::magic_close).
+    pub fn magic_close_count(&self) -> usize{
+        self.magic_close_count
+    }
+}
+
+impl KeepAlive for MockWatchdog {
+    fn keep_alive(&mut self) -> Result<(), WatchdogError>{
+        if let Some(errno) = self.keep_alive_errors.pop_front(){
+            trace!("Mock keep alive failed:{}", errno);
+            return Err(errno.into());
+        }
+        // The reset could already have happened before this ping.
+        self.expired = self.has_expired();
+        self.keep_alive_count += 1;
+        self.last_keep_alive = Instant::now();
+        self.closed = false;
+        Ok(())
+    }
+
+    fn get_timeout(&self) -> Result<i32, WatchdogError>{
+        Ok(self.timeout)
+    }
+
+    fn set_timeout(&mut self, timeout: i32) -> Result<i32, WatchdogError>{
+        self.set_timeout_calls.push(timeout);
+        if let Some(errno) = self.set_timeout_error.take(){
+            return Err(errno.into());
+        }
+        if timeout <= 0{
+            // As the drivers do.
+            return Err(Errno::EINVAL.into());
+        }
+        self.timeout = timeout;
+        Ok(timeout)
+    }
+
+    fn magic_close(&mut self) -> Result<(), WatchdogError>{
+        self.magic_close_count += 1;
+        // The reset could already have happened before the magic close.
+        self.expired = self.has_expired();
+        self.closed = true;
+        Ok(())
+    }
+}
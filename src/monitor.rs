@@ -0,0 +1,87 @@
+//! Keep alive monitoring facilities.
+//!
+//! In a cooperative main loop, the application calls [`keep_alive()`](crate::watchdog_device::Watchdog::keep_alive) itself,
+//! and its period can drift towards the timeout (e.g. a slow iteration) long before a reset actually happens.
+//! [`WatchdogMonitor`] reports the pings that came dangerously close to the deadline, as an early warning.
+use std::time::Duration;
+use crate::watchdog_device::Watchdog;
+use crate::error::WatchdogError;
+
+/// Watchdog reporting the keep alive pings that came late.
+///
+/// A ping is late if it comes more than `timeout - warn_margin` after the previous successful ping (or the activation):
+/// see [`Watchdog::set_warn_margin()`], which this relies on. Each late ping is logged as a warning,
+/// and reported by [`ping()`](Self::ping). The timeout is the [cached one](Watchdog::cached_timeout):
+/// if it is unknown, no ping is late.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{Watchdog, WatchdogMonitor};
+/// use std::time::Duration;
+///
+/// # fn do_something(){}
+/// # fn main() -> Result<(), std::io::Error> {
+/// let mut monitor = WatchdogMonitor::new(Watchdog::new()?, Duration::from_secs(5));
+/// # let mut keep_running = true;
+/// while keep_running{
+///     do_something();
+///     if monitor.ping()?{
+///         println!("The loop is running late: {} late pings so far.", monitor.late_pings());
+///     }
+/// #   keep_running = false;
+/// }
+/// # monitor.watchdog_mut().magic_close()?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct WatchdogMonitor{
+    watchdog: Watchdog,
+    late_pings: u64,
+}
+
+impl WatchdogMonitor {
+    /// Wraps the watchdog, reporting the pings coming later than the timeout minus `warn_margin`.
+    pub fn new(mut watchdog: Watchdog, warn_margin: Duration) -> Self{
+        watchdog.set_warn_margin(warn_margin);
+        Self{watchdog, late_pings: 0}
+    }
+
+    /// Keeps the system alive, and tells if the ping came late.
+    ///
+    /// See [`Watchdog::keep_alive()`]. A failed ping is not counted as late, since it is reported as an error.
+    pub fn ping(&mut self) -> Result<bool, WatchdogError>{
+        self.watchdog.keep_alive()?;
+        if self.watchdog.last_keep_alive_late{
+            self.late_pings = self.late_pings.saturating_add(1);
+        }
+        Ok(self.watchdog.last_keep_alive_late)
+    }
+
+    /// Returns the number of late pings so far. Saturating.
+    pub fn late_pings(&self) -> u64{
+        self.late_pings
+    }
+
+    /// Returns the time elapsed since the last successful ping, if any.
+    ///
+    /// See [`Watchdog::time_since_last_keep_alive()`].
+    pub fn time_since_last_ping(&self) -> Option<Duration>{
+        self.watchdog.time_since_last_keep_alive()
+    }
+
+    /// Returns the monitored watchdog.
+    pub fn watchdog(&self) -> &Watchdog{
+        &self.watchdog
+    }
+
+    /// Returns the monitored watchdog, e.g. to configure it.
+    pub fn watchdog_mut(&mut self) -> &mut Watchdog{
+        &mut self.watchdog
+    }
+
+    /// Returns the watchdog, which keeps warning about the late pings (see [`Watchdog::set_warn_margin()`]).
+    pub fn into_inner(self) -> Watchdog{
+        self.watchdog
+    }
+}
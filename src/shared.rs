@@ -0,0 +1,338 @@
+//! Watchdog sharing facilities.
+//!
+//! A [`Watchdog`] is usually shared between threads by wrapping it in an `Arc<Mutex<Watchdog>>`,
+//! e.g. to keep it alive with [`start_automatic_keep_alive()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive).
+//! In that case any status query has to contend for the same lock used by the keep alive thread,
+//! even though the driver does not need the two to be serialized.
+//!
+//! Read-only ioctls do not interfere with the keep alive pings, so they can be issued on a duplicated
+//! file descriptor instead: see [`SharedWatchdog::status_reader()`].
+use std::fs::File;
+use std::thread::JoinHandle;
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError};
+#[cfg(unix)]
+use std::os::unix::io::AsRawFd;
+#[cfg(not(unix))]
+use crate::ioctl::AsRawFd;
+use crate::errno::Errno;
+use crate::watchdog_device::{Watchdog, OptionFlags, BitmaskQueryType, IntGetterType, KeepAliveExit};
+use log::error;
+use crate::error::WatchdogError;
+
+/// Watchdog shared between threads.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{Watchdog, SharedWatchdog};
+/// use log::info;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// let shared = SharedWatchdog::new(Watchdog::new()?);
+/// let handle = shared.start_automatic_keep_alive();
+/// let reader = shared.status_reader()?;
+/// // Polling the status never delays the keep alive thread.
+/// info!("Time left:{}", reader.get_time_left().unwrap());
+/// # shared.lock()?.magic_close()?;
+/// handle.join().expect("Error joining thread.");
+/// # Ok(())
+/// # }
+/// ```
+pub struct SharedWatchdog{
+    watchdog: Arc<Mutex<Watchdog>>,
+}
+
+impl SharedWatchdog {
+    /// Wraps the watchdog, so that it can be shared between threads.
+    pub fn new(watchdog: Watchdog) -> Self{
+        Self{watchdog: Arc::new(Mutex::new(watchdog))}
+    }
+
+    /// Returns the shared watchdog, e.g. to be moved into another thread.
+    pub fn watchdog(&self) -> Arc<Mutex<Watchdog>>{
+        self.watchdog.clone()
+    }
+
+    /// Locks the watchdog, blocking the current thread until the lock is available.
+    ///
+    /// If another thread panicked while holding the lock, the mutex is poisoned and 
+    /// [`WatchdogError::Poisoned`] is returned: the watchdog could have been left in an unexpected state
+    /// (e.g. half-way through a configuration change), so the caller decides how to proceed.
+    /// See [`lock_or_recover()`](Self::lock_or_recover) to ignore the poisoning instead.
+    pub fn lock(&self) -> Result<MutexGuard<'_, Watchdog>, WatchdogError>{
+        self.watchdog.lock().map_err(|_| WatchdogError::Poisoned)
+    }
+
+    /// Locks the watchdog, blocking the current thread until the lock is available, even if the mutex is poisoned.
+    ///
+    /// Unlike [`lock()`](Self::lock), a poisoned mutex is recovered and the lock is granted anyway.
+    /// This is appropriate for a safety-critical feeder, which must keep pinging the watchdog
+    /// regardless of a panic elsewhere: the watchdog state is just a file descriptor and a few settings,
+    /// so it can hardly be left inconsistent. Note that this also defeats the purpose of the poisoning:
+    /// a keep alive loop recovering the lock can keep the system alive even if the rest of the program is broken.
+    pub fn lock_or_recover(&self) -> MutexGuard<'_, Watchdog>{
+        self.watchdog.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Starts automatically keeping the system alive.
+    ///
+    /// See [`Watchdog::start_automatic_keep_alive()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive).
+    pub fn start_automatic_keep_alive(&self) -> JoinHandle<KeepAliveExit>{
+        Watchdog::start_automatic_keep_alive(self.watchdog.clone())
+    }
+
+    /// Stops the keep alive thread and returns the watchdog, e.g. for a clean close.
+    ///
+    /// The keep alive thread (if any) is signaled to exit before its next ping, and then joined through its handle,
+    /// as returned by [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive).
+    /// Pass `None` if no thread was started.
+    /// Once the thread is gone, the watchdog is unwrapped from the shared reference.
+    /// If other references still exist (see [`watchdog()`](Self::watchdog)), the shared watchdog is returned as the error instead.
+    ///
+    /// Note that the watchdog stays active: it must be pinged or closed with 
+    /// [`magic_close()`](crate::watchdog_device::Watchdog::magic_close), as usual.
+    /// A poisoned mutex is not considered an error here, since releasing the watchdog is exactly what is needed in that case.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::{Watchdog, SharedWatchdog};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let shared = SharedWatchdog::new(Watchdog::new()?);
+    /// let handle = shared.start_automatic_keep_alive();
+    /// // ...
+    /// let mut wd = shared.shutdown(Some(handle)).ok().expect("Watchdog still shared.");
+    /// wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn shutdown(self, keep_alive_handle: Option<JoinHandle<KeepAliveExit>>) -> Result<Watchdog, Self>{
+        self.lock_or_recover().stop_automatic_keep_alive();
+        if let Some(handle) = keep_alive_handle{
+            // A panic of the thread still released its reference.
+            let _ = handle.join();
+        }
+        match Arc::try_unwrap(self.watchdog){
+            Ok(mutex) => Ok(mutex.into_inner().unwrap_or_else(PoisonError::into_inner)),
+            Err(watchdog) => Err(Self{watchdog}),
+        }
+    }
+
+    /// Returns a handle that can only query the status of the watchdog.
+    ///
+    /// The handle works on a duplicate of the watchdog file descriptor, so its queries never lock
+    /// the shared watchdog and never delay the keep alive pings.
+    /// The watchdog is locked only once, while duplicating the file descriptor: a poisoned mutex is recovered,
+    /// since the descriptor is not affected by it.
+    ///
+    /// Beware that the duplicate refers to the same open file: the driver releases the device only once 
+    /// the [`Watchdog`] instance AND every reader are dropped. A reader outliving the magic close 
+    /// (see [`Watchdog::magic_close()`](crate::watchdog_device::Watchdog::magic_close)) keeps the watchdog running, 
+    /// with nobody pinging it, so the system is reset after the timeout: the readers must be dropped before the close.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::{SharedWatchdog, Watchdog};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let shared = SharedWatchdog::new(Watchdog::new()?);
+    /// let reader = shared.status_reader()?;
+    /// println!("Time left: {:?}", reader.get_time_left());
+    /// // Otherwise the device would not be released by the magic close.
+    /// drop(reader);
+    /// let mut wd = shared.shutdown(None).ok().expect("The watchdog is still shared.");
+    /// wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn status_reader(&self) -> Result<StatusReader, WatchdogError>{
+        let file = self.lock_or_recover().file.try_clone()?;
+        Ok(StatusReader{file})
+    }
+}
+
+/// Read-only handle to a watchdog, returned by [`SharedWatchdog::status_reader()`].
+///
+/// All the methods mirror the equivalent ones of [`Watchdog`]; see their documentation for more information.
+/// It keeps the device open: drop it before the magic close (see [`SharedWatchdog::status_reader()`]).
+pub struct StatusReader{
+    file: File,
+}
+
+impl StatusReader {
+    /// See [`Watchdog::get_status()`](crate::watchdog_device::Watchdog::get_status).
+    pub fn get_status(&self, option: &OptionFlags) -> Result<bool, WatchdogError> {
+        Ok(Watchdog::bitmask_query(self.file.as_raw_fd(), option, &BitmaskQueryType::GetStatus)?)
+    }
+
+    /// See [`Watchdog::get_boot_status()`](crate::watchdog_device::Watchdog::get_boot_status).
+    ///
+    /// Unlike the watchdog, the reader does not cache the boot status, so this always queries the driver.
+    pub fn get_boot_status(&self, option: &OptionFlags) -> Result<bool, WatchdogError> {
+        Ok(Watchdog::bitmask_query(self.file.as_raw_fd(), option, &BitmaskQueryType::GetBootStatus)?)
+    }
+
+    /// See [`Watchdog::get_timeout()`](crate::watchdog_device::Watchdog::get_timeout).
+    pub fn get_timeout(&self) -> Result<i32, WatchdogError> {
+        Ok(Watchdog::int_getter(self.file.as_raw_fd(), IntGetterType::Timeout)?)
+    }
+
+    /// See [`Watchdog::get_pretimeout()`](crate::watchdog_device::Watchdog::get_pretimeout).
+    pub fn get_pretimeout(&self) -> Result<i32, WatchdogError> {
+        Ok(Watchdog::int_getter(self.file.as_raw_fd(), IntGetterType::PreTimeout)?)
+    }
+
+    /// See [`Watchdog::get_time_left()`](crate::watchdog_device::Watchdog::get_time_left).
+    pub fn get_time_left(&self) -> Result<i32, WatchdogError> {
+        Ok(Watchdog::int_getter(self.file.as_raw_fd(), IntGetterType::TimeLeft)?)
+    }
+
+    /// See [`Watchdog::get_temp()`](crate::watchdog_device::Watchdog::get_temp).
+    pub fn get_temp(&self) -> Result<i32, WatchdogError> {
+        Ok(Watchdog::int_getter(self.file.as_raw_fd(), IntGetterType::Temp)?)
+    }
+}
+
+/// Statistics of a [`WatchdogWorker`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct WorkerStats{
+    /// Number of successful keep alive pings.
+    pub keep_alives: u64,
+    /// Number of failed keep alive pings.
+    pub errors: u64,
+    /// Error of the last failed ping, if any.
+    pub last_error: Option<Errno>,
+}
+
+/// Thread keeping a watchdog alive, returned by [`Watchdog::keepalive_loop_in_thread()`](crate::watchdog_device::Watchdog::keepalive_loop_in_thread).
+///
+/// The worker owns the watchdog: [`stop()`](Self::stop) ends the thread and gives it back.
+/// If the worker is dropped instead, the thread is signaled to exit and the watchdog is released 
+/// without any magic close, so the system will reset after the timeout.
+pub struct WatchdogWorker{
+    shared: Option<SharedWatchdog>,
+    handle: Option<JoinHandle<KeepAliveExit>>,
+    stats: Arc<Mutex<WorkerStats>>,
+}
+
+impl WatchdogWorker {
+    pub(crate) fn new(shared: SharedWatchdog, handle: JoinHandle<KeepAliveExit>, stats: Arc<Mutex<WorkerStats>>) -> Self{
+        Self{shared: Some(shared), handle: Some(handle), stats}
+    }
+
+    /// Returns the statistics of the pings sent so far.
+    pub fn stats(&self) -> WorkerStats{
+        *self.stats.lock().unwrap_or_else(PoisonError::into_inner)
+    }
+
+    /// Stops the thread and returns the watchdog, e.g. to close it with 
+    /// [`magic_close()`](crate::watchdog_device::Watchdog::magic_close).
+    ///
+    /// See [`SharedWatchdog::shutdown()`].
+    pub fn stop(mut self) -> Watchdog{
+        let shared = self.shared.take().expect("The watchdog is only taken when stopping.");
+        match shared.shutdown(self.handle.take()){
+            Ok(watchdog) => watchdog,
+            // The only other reference is owned by the thread, which was joined.
+            Err(_) => unreachable!("The watchdog of a worker is never shared."),
+        }
+    }
+}
+
+impl Drop for WatchdogWorker {
+    fn drop(&mut self) {
+        if let Some(shared) = &self.shared{
+            shared.lock_or_recover().stop_automatic_keep_alive();
+        }
+    }
+}
+
+/// Scope guard keeping a watchdog alive, returned by [`Watchdog::keep_alive_guard()`](crate::watchdog_device::Watchdog::keep_alive_guard).
+///
+/// The guard owns the watchdog, and a thread pinging it on a fixed interval. When the guard is dropped, in this order:
+/// 1. the thread is signaled to exit and joined, so no ping is sent afterwards;
+/// 2. if enabled with [`magic_close_on_drop()`](Self::magic_close_on_drop) and supported by the driver, 
+///    the watchdog is magic closed;
+/// 3. the watchdog is dropped, closing the device (see [`Watchdog::set_on_drop()`](crate::watchdog_device::Watchdog::set_on_drop)).
+///
+/// Without the magic close, the watchdog stays active after the guard is gone, so the system is reset after the timeout,
+/// unless the driver stops it on close. Note that `let _ = wd.keep_alive_guard(...)` drops the guard right away:
+/// bind it to a named variable (e.g. `_guard`) to keep it for the whole scope.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::Watchdog;
+/// use std::time::Duration;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// {
+///     let _guard = Watchdog::new()?.keep_alive_guard(Duration::from_secs(2)).magic_close_on_drop(true);
+///     // The watchdog is pinged until the end of the scope, and then stopped.
+/// }
+/// # Ok(())
+/// # }
+/// ```
+#[must_use = "the pings stop as soon as the guard is dropped"]
+pub struct KeepAliveGuard{
+    shared: Option<SharedWatchdog>,
+    handle: Option<JoinHandle<KeepAliveExit>>,
+    magic_close_on_drop: bool,
+}
+
+impl KeepAliveGuard {
+    pub(crate) fn new(shared: SharedWatchdog, handle: JoinHandle<KeepAliveExit>) -> Self{
+        Self{shared: Some(shared), handle: Some(handle), magic_close_on_drop: false}
+    }
+
+    /// Magic closes the watchdog when the guard is dropped, if supported by the driver (disabled by default).
+    pub fn magic_close_on_drop(mut self, enable: bool) -> Self{
+        self.magic_close_on_drop = enable;
+        self
+    }
+
+    /// Locks the watchdog, e.g. to query its status while the thread keeps pinging it.
+    ///
+    /// See [`SharedWatchdog::lock_or_recover()`]: holding the lock delays the pings.
+    pub fn lock(&self) -> MutexGuard<'_, Watchdog>{
+        self.shared.as_ref().expect("The watchdog is only taken when dropping.").lock_or_recover()
+    }
+
+    /// Tells if the ping thread ended, e.g. after too many consecutive errors.
+    pub fn is_finished(&self) -> bool{
+        self.handle.as_ref().map_or(true, |handle| handle.is_finished())
+    }
+
+    /// Stops the thread and returns the watchdog, without any magic close.
+    ///
+    /// See [`SharedWatchdog::shutdown()`].
+    pub fn stop(mut self) -> Watchdog{
+        self.take_watchdog()
+    }
+
+    /// Stops and joins the thread, and unwraps the watchdog.
+    fn take_watchdog(&mut self) -> Watchdog{
+        let shared = self.shared.take().expect("The watchdog is only taken when dropping.");
+        match shared.shutdown(self.handle.take()){
+            Ok(watchdog) => watchdog,
+            // The only other reference is owned by the thread, which was joined.
+            Err(_) => unreachable!("The watchdog of a guard is never shared."),
+        }
+    }
+}
+
+impl Drop for KeepAliveGuard {
+    fn drop(&mut self) {
+        if self.shared.is_none(){
+            return;
+        }
+        let mut watchdog = self.take_watchdog();
+        if self.magic_close_on_drop && watchdog.is_option_supported(&OptionFlags::MagicClose).unwrap_or(false){
+            if let Err(e) = watchdog.magic_close(){
+                error!("Magic close on drop failed:{}", e);
+            }
+        }
+    }
+}
@@ -0,0 +1,360 @@
+//! sysfs facilities.
+//!
+//! Besides the character device, the Linux Kernel exposes the state of every watchdog
+//! under `/sys/class/watchdog/watchdogN/`, with a read-only file per attribute
+//! (e.g. `identity`, `options`, `timeout`, `nowayout`).
+//! Unlike opening `/dev/watchdog*`, reading these files does NOT arm the watchdog,
+//! so they can be used to safely inspect a device.
+//!
+//! Kernel documentation: /Documentation/ABI/testing/sysfs-class-watchdog
+use log::{info, trace};
+use std::fs::{self, File};
+use std::io;
+use std::path::{Path, PathBuf};
+#[cfg(unix)]
+use std::os::unix::fs::MetadataExt;
+use crate::watchdog_device::{Watchdog, OptionFlags, OptionSet, PretimeoutGovernor};
+use crate::error::WatchdogError;
+
+const SYSFS_CLASS_DIR: &str = "/sys/class/watchdog";
+const SYSFS_DEV_CHAR_DIR: &str = "/sys/dev/char";
+
+// The legacy '/dev/watchdog' node is a misc device (see include/linux/miscdevice.h),
+// which is always bound to the watchdog with ID 0.
+const MISC_MAJOR: u32 = 10;
+const WATCHDOG_MINOR: u32 = 130;
+
+/// Static information about a watchdog, as returned by [`inspect()`].
+#[derive(Debug, Clone)]
+pub struct StaticInfo {
+    /// A string identifying the watchdog driver
+    pub identity: String,
+    /// Options supported by the driver
+    pub options: OptionSet,
+    /// The firmware version of the card, if available
+    pub firmware_version: Option<u32>,
+    /// The configured timeout in seconds, if exposed by sysfs
+    pub timeout: Option<u32>,
+    /// Whether the watchdog can be stopped once started, if exposed by sysfs
+    pub nowayout: Option<bool>,
+}
+
+impl StaticInfo {
+    /// Tells if an option is supported, according to the gathered options.
+    pub fn is_option_supported(&self, option: &OptionFlags) -> bool {
+        self.options.contains(*option)
+    }
+
+    /// Returns the options bitmask, including the bits unknown to this crate
+    /// (see [`OptionSet::unknown_bits()`]).
+    pub fn options_raw(&self) -> u32 {
+        self.options.bits()
+    }
+}
+
+/// Gathers the static information of a watchdog without arming it.
+///
+/// The identity and the supported options are read from the sysfs directory related to the device
+/// found at the given path (e.g. '/dev/watchdog0'), without opening the device itself.
+/// The sysfs directory is resolved by device number, so symlinks and renamed nodes are handled correctly.
+///
+/// Not all kernels expose every attribute (e.g. `options` and `fw_version` are fairly recent).
+/// If the identity or the options are missing from sysfs, this falls back to opening the device
+/// and issuing the `WDIOC_GETSUPPORT` ioctl (see [`Watchdog::probe_path()`]): in that case the watchdog IS armed for a brief moment,
+/// and is released right away with [`magic_close()`](Watchdog::magic_close) if supported.
+/// Beware that on drivers with 'nowayout' set, the fallback leaves the watchdog armed.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use watchdog_device::inspect;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// let info = inspect("/dev/watchdog0")?;
+/// println!("{} supports options {}", info.identity, info.options);
+/// # Ok(())
+/// # }
+/// ```
+pub fn inspect<P: AsRef<Path>>(path: P) -> Result<StaticInfo, WatchdogError> {
+    let path = path.as_ref();
+    let dir = sysfs_dir_for_path(path)?;
+    let identity = read_attribute(&dir, "identity").ok();
+    let options = read_attribute(&dir, "options").ok().and_then(|v| parse_hex(&v));
+    let mut firmware_version = read_attribute(&dir, "fw_version").ok().and_then(|v| v.parse().ok());
+    let timeout = read_attribute(&dir, "timeout").ok().and_then(|v| v.parse().ok());
+    let nowayout = read_attribute(&dir, "nowayout").ok().map(|v| v != "0");
+
+    let (identity, options) = match (identity, options) {
+        (Some(identity), Some(options)) => (identity, options),
+        _ => {
+            info!("sysfs information incomplete for {}. Opening the device to inspect it.", path.display());
+            let wd_info = Watchdog::probe_path(path)?;
+            firmware_version = firmware_version.or(Some(wd_info.firmware_version));
+            (wd_info.identity, wd_info.options.bits())
+        }
+    };
+    Ok(StaticInfo{identity, options: OptionSet::from_bits(options), firmware_version, timeout, nowayout})
+}
+
+/// Read-only view of a watchdog through its sysfs attributes.
+///
+/// Every attribute is read on demand from `/sys/class/watchdog/watchdogN/`, and the device itself is never opened,
+/// so this is the safe way for monitoring tools to inspect a watchdog they do not own.
+/// Attributes that are not exposed by the kernel or the driver are reported with [`io::ErrorKind::Unsupported`].
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use watchdog_device::SysfsWatchdog;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// let wd = SysfsWatchdog::new(0);
+/// println!("{} is {}, timeout {}s", wd.identity()?, wd.state()?, wd.timeout()?);
+/// # Ok(())
+/// # }
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SysfsWatchdog {
+    dir: PathBuf,
+}
+
+impl SysfsWatchdog {
+    /// Refers to the watchdog with the given ID (e.g. 1 for '/dev/watchdog1').
+    ///
+    /// Nothing is read here: a watchdog that does not exist makes all the attributes unsupported.
+    pub fn new(id: u8) -> Self {
+        Self{dir: Path::new(SYSFS_CLASS_DIR).join(format!("watchdog{id}"))}
+    }
+
+    /// Returns the sysfs directory of the watchdog.
+    pub fn dir(&self) -> &Path {
+        &self.dir
+    }
+
+    /// Returns the identity of the driver.
+    pub fn identity(&self) -> Result<String, WatchdogError> {
+        Ok(read_attribute(&self.dir, "identity")?)
+    }
+
+    /// Returns the configured timeout in seconds.
+    pub fn timeout(&self) -> Result<u32, WatchdogError> {
+        self.parsed_attribute("timeout")
+    }
+
+    /// Returns the configured pretimeout in seconds.
+    pub fn pretimeout(&self) -> Result<u32, WatchdogError> {
+        self.parsed_attribute("pretimeout")
+    }
+
+    /// Returns the number of seconds left before the reset.
+    pub fn timeleft(&self) -> Result<u32, WatchdogError> {
+        self.parsed_attribute("timeleft")
+    }
+
+    /// Returns the state of the watchdog, i.e. "active" or "inactive".
+    pub fn state(&self) -> Result<String, WatchdogError> {
+        Ok(read_attribute(&self.dir, "state")?)
+    }
+
+    /// Returns the status bitmask of the reason for the last reboot (see [`OptionFlags`]).
+    pub fn bootstatus(&self) -> Result<u32, WatchdogError> {
+        self.parsed_attribute("bootstatus")
+    }
+
+    /// Returns the current status bitmask (see [`OptionFlags`]).
+    pub fn status(&self) -> Result<u32, WatchdogError> {
+        let value = read_attribute(&self.dir, "status")?;
+        parse_hex(&value).ok_or_else(|| invalid_attribute("status", &value))
+    }
+
+    /// Tells if the watchdog cannot be stopped once started.
+    pub fn nowayout(&self) -> Result<bool, WatchdogError> {
+        Ok(self.parsed_attribute::<u32>("nowayout")? != 0)
+    }
+
+    /// Returns the active pretimeout governor.
+    ///
+    /// See [`Watchdog::active_pretimeout_governor()`].
+    pub fn pretimeout_governor(&self) -> Result<PretimeoutGovernor, WatchdogError> {
+        active_governor(&self.dir)
+    }
+
+    /// Returns the pretimeout governors available to the watchdog.
+    ///
+    /// See [`Watchdog::available_pretimeout_governors()`].
+    pub fn available_pretimeout_governors(&self) -> Result<Vec<PretimeoutGovernor>, WatchdogError> {
+        available_governors(&self.dir)
+    }
+
+    /// Selects the pretimeout governor, without opening the device.
+    ///
+    /// See [`Watchdog::set_pretimeout_governor()`]: the governor must be one of the available ones.
+    pub fn set_pretimeout_governor(&self, governor: &PretimeoutGovernor) -> Result<(), WatchdogError> {
+        set_governor(&self.dir, governor)
+    }
+
+    /// Reads a decimal attribute.
+    fn parsed_attribute<T: std::str::FromStr>(&self, name: &str) -> Result<T, WatchdogError> {
+        let value = read_attribute(&self.dir, name)?;
+        value.parse().map_err(|_| invalid_attribute(name, &value))
+    }
+}
+
+/// Reports an attribute value that could not be parsed.
+fn invalid_attribute(name: &str, value: &str) -> WatchdogError {
+    io::Error::new(io::ErrorKind::InvalidData, format!("invalid value of sysfs attribute {name}: '{value}'")).into()
+}
+
+/// Returns the name (e.g. 'watchdog1') of the first watchdog with the given identity.
+pub(crate) fn find_by_identity(identity: &str) -> io::Result<Option<String>> {
+    let mut names: Vec<String> = fs::read_dir(SYSFS_CLASS_DIR)?
+        .filter_map(|entry| entry.ok())
+        .map(|entry| entry.file_name().to_string_lossy().into_owned())
+        .collect();
+    names.sort();
+    Ok(names.into_iter().find(|name| {
+        read_attribute(&Path::new(SYSFS_CLASS_DIR).join(name), "identity").is_ok_and(|value| value == identity)
+    }))
+}
+
+/// Returns the sysfs directory of the watchdog represented by the device file at the given path.
+pub(crate) fn sysfs_dir_for_path(path: &Path) -> io::Result<PathBuf> {
+    // 'metadata' follows symlinks, and does not open the device.
+    let rdev = rdev(&fs::metadata(path)?)?;
+    Ok(sysfs_dir_for_rdev(rdev))
+}
+
+/// Returns the sysfs directory of the watchdog represented by an open device file.
+pub(crate) fn sysfs_dir_for_file(file: &File) -> io::Result<PathBuf> {
+    let rdev = rdev(&file.metadata()?)?;
+    Ok(sysfs_dir_for_rdev(rdev))
+}
+
+/// Returns the device number of a device file.
+#[cfg(unix)]
+pub(crate) fn rdev(metadata: &fs::Metadata) -> io::Result<u64> {
+    Ok(metadata.rdev())
+}
+
+/// Fails with [`io::ErrorKind::Unsupported`], since device numbers only exist on Unix.
+#[cfg(not(unix))]
+pub(crate) fn rdev(_metadata: &fs::Metadata) -> io::Result<u64> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "device numbers are only available on Unix"))
+}
+
+/// Tells if the device number is the one of the legacy '/dev/watchdog' node, bound to the watchdog with ID 0.
+pub(crate) fn is_legacy_rdev(rdev: u64) -> bool {
+    major_minor(rdev) == (MISC_MAJOR, WATCHDOG_MINOR)
+}
+
+/// Splits a device number into its major and minor numbers.
+#[cfg(target_os = "linux")]
+pub(crate) fn major_minor(rdev: u64) -> (u32, u32) {
+    (libc::major(rdev), libc::minor(rdev))
+}
+
+/// Splits a device number into its major and minor numbers.
+///
+/// sysfs only exists on Linux, so the numbers are only used for display: the traditional encoding is assumed.
+#[cfg(not(target_os = "linux"))]
+pub(crate) fn major_minor(rdev: u64) -> (u32, u32) {
+    (((rdev >> 8) & 0xfff) as u32, (rdev & 0xff) as u32)
+}
+
+/// Returns the sysfs directory of the watchdog with the given device number.
+pub(crate) fn sysfs_dir_for_rdev(rdev: u64) -> PathBuf {
+    let (major, minor) = major_minor(rdev);
+    if is_legacy_rdev(rdev) {
+        Path::new(SYSFS_CLASS_DIR).join("watchdog0")
+    }
+    else {
+        Path::new(SYSFS_DEV_CHAR_DIR).join(format!("{major}:{minor}"))
+    }
+}
+
+/// Reads a sysfs attribute, trimming the trailing newline.
+///
+/// A missing attribute file is reported with [`io::ErrorKind::Unsupported`].
+pub(crate) fn read_attribute(dir: &Path, name: &str) -> io::Result<String> {
+    let attribute_path = dir.join(name);
+    match fs::read_to_string(&attribute_path) {
+        Ok(value) => {
+            trace!("{}: {}", attribute_path.display(), value.trim_end());
+            Ok(value.trim_end().to_string())
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("sysfs attribute {} not available", attribute_path.display()))),
+        Err(e) => Err(e),
+    }
+}
+
+/// Writes a sysfs attribute.
+///
+/// A missing attribute file is reported with [`io::ErrorKind::Unsupported`].
+pub(crate) fn write_attribute(dir: &Path, name: &str, value: &str) -> io::Result<()> {
+    let attribute_path = dir.join(name);
+    match fs::write(&attribute_path, value) {
+        Ok(_) => {
+            trace!("{} <- {}", attribute_path.display(), value);
+            Ok(())
+        },
+        Err(e) if e.kind() == io::ErrorKind::NotFound => Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("sysfs attribute {} not available", attribute_path.display()))),
+        Err(e) => Err(e),
+    }
+}
+
+/// Parses hexadecimal values such as "0x8180", as printed by the kernel for bitmasks.
+pub(crate) fn parse_hex(value: &str) -> Option<u32> {
+    u32::from_str_radix(value.trim_start_matches("0x"), 16).ok()
+}
+
+/// Returns the pretimeout governors available to the watchdog with the given sysfs directory.
+pub(crate) fn available_governors(dir: &Path) -> Result<Vec<PretimeoutGovernor>, WatchdogError> {
+    let (governors, _) = parse_governors(&read_attribute(dir, "pretimeout_available_governors")?);
+    Ok(governors.iter().map(|governor| PretimeoutGovernor::from(governor.as_str())).collect())
+}
+
+/// Returns the active pretimeout governor of the watchdog with the given sysfs directory.
+///
+/// The governor marked in the list of the available ones is preferred, falling back to 'pretimeout_governor'.
+pub(crate) fn active_governor(dir: &Path) -> Result<PretimeoutGovernor, WatchdogError> {
+    let active = read_attribute(dir, "pretimeout_available_governors").ok()
+        .and_then(|list| parse_governors(&list).1);
+    let active = match active {
+        Some(active) => active,
+        None => read_attribute(dir, "pretimeout_governor")?,
+    };
+    Ok(PretimeoutGovernor::from(active.as_str()))
+}
+
+/// Selects the pretimeout governor of the watchdog with the given sysfs directory, if available.
+pub(crate) fn set_governor(dir: &Path, governor: &PretimeoutGovernor) -> Result<(), WatchdogError> {
+    let available = available_governors(dir)?;
+    if !available.contains(governor) {
+        let names: Vec<String> = available.iter().map(|governor| governor.to_string()).collect();
+        return Err(io::Error::new(io::ErrorKind::InvalidInput, 
+            format!("pretimeout governor {} not available (available: {})", governor, names.join(", "))).into());
+    }
+    Ok(write_attribute(dir, "pretimeout_governor", &governor.to_string())?)
+}
+
+/// Parses a list of pretimeout governors, returning all of them and the active one, if marked.
+///
+/// The kernel prints one governor per line, but the list is also accepted separated by spaces,
+/// with the active governor enclosed in brackets (e.g. "noop [panic]"), as other sysfs lists do.
+pub(crate) fn parse_governors(value: &str) -> (Vec<String>, Option<String>) {
+    let mut active = None;
+    let governors = value.split_whitespace()
+        .map(|governor| match governor.strip_prefix('[').and_then(|g| g.strip_suffix(']')) {
+            Some(bracketed) => {
+                active = Some(bracketed.to_string());
+                bracketed.to_string()
+            },
+            None => governor.to_string(),
+        })
+        .collect();
+    (governors, active)
+}
@@ -0,0 +1,99 @@
+//! systemd watchdog facilities.
+//!
+//! Services with the `WatchdogSec=` setting are supervised by systemd, which expects periodic
+//! `WATCHDOG=1` notifications on the socket indicated by the `NOTIFY_SOCKET` environment variable
+//! (see `sd_notify(3)` and `sd_watchdog_enabled(3)`).
+//! This is independent of the watchdog device, but the two can be fed by the same loop.
+use log::{trace, warn};
+use std::env;
+use std::io;
+use std::process;
+use std::time::{Duration, Instant};
+#[cfg(unix)]
+use std::os::unix::net::{SocketAddr, UnixDatagram};
+#[cfg(target_os = "linux")]
+use std::os::linux::net::SocketAddrExt;
+
+/// Returns the systemd watchdog interval configured for this process, if enabled.
+///
+/// The interval is read from `WATCHDOG_USEC`, as set by systemd for services with `WatchdogSec=`. 
+/// If `WATCHDOG_PID` is set, it must match the current process.
+/// The service manager must be notified more often than this: 
+/// [`Watchdog::start_automatic_keep_alive_with_systemd()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive_with_systemd)
+/// does it at half of the interval, while pinging the watchdog device.
+///
+/// Requires the `systemd` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::systemd_watchdog_interval;
+///
+/// match systemd_watchdog_interval(){
+///     Some(interval) => println!("systemd expects a notification every {:?}", interval),
+///     None => println!("systemd watchdog not enabled"),
+/// }
+/// ```
+pub fn systemd_watchdog_interval() -> Option<Duration> {
+    if let Ok(pid) = env::var("WATCHDOG_PID") {
+        if pid.parse::<u32>().ok() != Some(process::id()) {
+            return None;
+        }
+    }
+    let usec = env::var("WATCHDOG_USEC").ok()?.parse::<u64>().ok()?;
+    if usec == 0 {
+        return None;
+    }
+    Some(Duration::from_micros(usec))
+}
+
+/// Sends a notification to the systemd service manager.
+#[cfg(unix)]
+pub(crate) fn notify(state: &str) -> io::Result<()> {
+    let socket_path = env::var_os("NOTIFY_SOCKET")
+        .ok_or_else(|| io::Error::new(io::ErrorKind::NotFound, "NOTIFY_SOCKET not set"))?;
+    let socket_path = socket_path.to_string_lossy();
+    let addr = match socket_path.strip_prefix('@') {
+        #[cfg(target_os = "linux")]
+        Some(abstract_name) => SocketAddr::from_abstract_name(abstract_name.as_bytes())?,
+        _ => SocketAddr::from_pathname(socket_path.as_ref())?,
+    };
+    let socket = UnixDatagram::unbound()?;
+    socket.send_to_addr(state.as_bytes(), &addr)?;
+    Ok(())
+}
+
+/// Fails with [`io::ErrorKind::Unsupported`], since the notification socket is a Unix socket.
+#[cfg(not(unix))]
+pub(crate) fn notify(_state: &str) -> io::Result<()> {
+    Err(io::Error::new(io::ErrorKind::Unsupported, "systemd notifications are only available on Unix"))
+}
+
+/// Sends the systemd watchdog notifications at the appropriate pace.
+pub(crate) struct WatchdogNotifier {
+    interval: Duration,
+    last_notification: Option<Instant>,
+}
+
+impl WatchdogNotifier {
+    /// Returns a notifier if the systemd watchdog is enabled for this process.
+    ///
+    /// The notifications are sent at half of the configured interval, as recommended by `sd_watchdog_enabled(3)`.
+    pub(crate) fn from_env() -> Option<Self> {
+        systemd_watchdog_interval().map(|interval| Self{interval: interval / 2, last_notification: None})
+    }
+
+    /// Notifies systemd, if half of the configured interval elapsed since the last notification.
+    pub(crate) fn notify_if_due(&mut self) {
+        if self.last_notification.is_some_and(|last| last.elapsed() < self.interval) {
+            return;
+        }
+        match notify("WATCHDOG=1") {
+            Ok(_) => {
+                trace!("systemd watchdog notified.");
+                self.last_notification = Some(Instant::now());
+            },
+            Err(e) => warn!("systemd watchdog notification error {}.", e),
+        }
+    }
+}
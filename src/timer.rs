@@ -0,0 +1,154 @@
+//! Async keep alive facilities.
+//!
+//! [`start_automatic_keep_alive()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive) spawns a thread,
+//! which is not always desirable in async programs. This module allows feeding the watchdog from a task instead,
+//! on any executor: the only dependency is a way to sleep, abstracted by [`WatchdogTimer`].
+//! With the `tokio` feature, [`TokioTimer`] provides it for tokio, and [`spawn_keep_alive()`] spawns a task owning the watchdog.
+use log::trace;
+#[cfg(feature = "tokio")]
+use log::{error, info};
+use std::future::Future;
+use std::time::Duration;
+#[cfg(feature = "tokio")]
+use crate::watchdog_device::Watchdog;
+use crate::watchdog_device::KeepAlive;
+use crate::error::WatchdogError;
+
+/// Timer used to wait between two keep alive pings.
+///
+/// Implement this to feed the watchdog from executors other than tokio (e.g. embassy-style ones),
+/// by returning their own sleep future.
+pub trait WatchdogTimer{
+    /// Returns a future that completes after the given duration.
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>;
+}
+
+/// Timer based on `tokio::time::sleep()`.
+///
+/// Requires the `tokio` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{Watchdog, TokioTimer, keep_alive_with_timer};
+/// use std::time::Duration;
+///
+/// async fn feed(wd: &mut Watchdog) -> Result<(), std::io::Error> {
+///     // Only returns if a ping fails.
+///     keep_alive_with_timer(wd, &TokioTimer, Duration::from_secs(1)).await?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+#[derive(Debug, Default, Clone, Copy)]
+pub struct TokioTimer;
+
+#[cfg(feature = "tokio")]
+impl WatchdogTimer for TokioTimer {
+    fn sleep(&self, duration: Duration) -> impl Future<Output = ()>{
+        tokio::time::sleep(duration)
+    }
+}
+
+/// Keeps the system alive from an async task, pinging once every `interval`.
+///
+/// This is the async counterpart of the automatic keep alive thread: the watchdog is pinged right away,
+/// and then after each wait on the timer. The future only completes when a ping fails, returning the error.
+/// To stop it, drop the future (e.g. by aborting the task, or with a `select!`), and then release the watchdog
+/// as usual (e.g. with [`magic_close()`](crate::watchdog_device::Watchdog::magic_close)).
+///
+/// The same disclaimer of [`start_automatic_keep_alive()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive) applies:
+/// if the rest of the program malfunctions while the task keeps running, the watchdog is still pinged.
+///
+/// See `TokioTimer` (`tokio` feature) for an example.
+pub async fn keep_alive_with_timer<W: KeepAlive, T: WatchdogTimer>(watchdog: &mut W, timer: &T, interval: Duration) -> Result<(), WatchdogError>{
+    loop{
+        watchdog.keep_alive()?;
+        trace!("Waiting {:?}...", interval);
+        timer.sleep(interval).await;
+    }
+}
+
+/// Spawns a tokio task keeping the system alive, pinging once every `interval`.
+///
+/// This is the tokio counterpart of [`start_automatic_keep_alive()`](crate::watchdog_device::Watchdog::start_automatic_keep_alive):
+/// the task owns the watchdog, so no mutex is needed, and no thread is spawned besides the ones of the runtime.
+/// The ioctls are blocking, so each ping runs on the blocking thread pool (see `tokio::task::spawn_blocking()`).
+/// The watchdog is pinged right away, and then after each interval.
+///
+/// The task is stopped by [`TokioKeepAlive::stop()`], which gives the watchdog back, e.g. for a
+/// [`magic_close()`](crate::watchdog_device::Watchdog::magic_close). Dropping the returned handle stops the task as well,
+/// but the watchdog is then released without any magic close, so the system will reset after the timeout.
+/// If a ping fails, the task ends and the watchdog is released in the same way.
+///
+/// Must be called from within a tokio runtime. Requires the `tokio` feature.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{Watchdog, spawn_keep_alive};
+/// use std::time::Duration;
+///
+/// async fn run() -> Result<(), std::io::Error> {
+///     let keep_alive = spawn_keep_alive(Watchdog::new()?, Duration::from_secs(1));
+///     // ...
+///     let mut wd = keep_alive.stop().await?;
+///     wd.magic_close()?;
+///     Ok(())
+/// }
+/// ```
+#[cfg(feature = "tokio")]
+pub fn spawn_keep_alive(watchdog: Watchdog, interval: Duration) -> TokioKeepAlive{
+    let (stop_sender, mut stop_receiver) = tokio::sync::oneshot::channel::<()>();
+    let handle = tokio::spawn(async move {
+        let mut watchdog = watchdog;
+        info!("Keep alive task started. Interval:{:?}.", interval);
+        loop{
+            let (wd, result) = tokio::task::spawn_blocking(move || {
+                let result = watchdog.keep_alive();
+                (watchdog, result)
+            }).await.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()));
+            watchdog = wd;
+            if let Err(e) = result{
+                error!("Keep alive task ended. error:{}", e);
+                return Err(e);
+            }
+            trace!("Waiting {:?}...", interval);
+            // Completes when the handle sends the stop message, or when it is dropped.
+            if tokio::time::timeout(interval, &mut stop_receiver).await.is_ok(){
+                info!("Keep alive task stopped.");
+                return Ok(watchdog);
+            }
+        }
+    });
+    TokioKeepAlive{handle, stop_sender}
+}
+
+/// Handle of the task spawned by [`spawn_keep_alive()`].
+///
+/// Unlike a bare `tokio::task::JoinHandle<()>`, it gives the watchdog back once the task is stopped,
+/// and it is its own cancellation mechanism: dropping the handle stops the task. Requires the `tokio` feature.
+#[cfg(feature = "tokio")]
+pub struct TokioKeepAlive{
+    handle: tokio::task::JoinHandle<Result<Watchdog, WatchdogError>>,
+    stop_sender: tokio::sync::oneshot::Sender<()>,
+}
+
+#[cfg(feature = "tokio")]
+impl TokioKeepAlive {
+    /// Stops the task and returns the watchdog, or the error that ended the task earlier.
+    ///
+    /// As for [`SharedWatchdog::shutdown()`](crate::shared::SharedWatchdog::shutdown), the watchdog stays active:
+    /// it must be pinged or closed as usual.
+    /// If the task panicked, the panic is propagated.
+    pub async fn stop(self) -> Result<Watchdog, WatchdogError>{
+        // The task could have already ended, dropping the receiver.
+        let _ = self.stop_sender.send(());
+        self.handle.await.unwrap_or_else(|e| std::panic::resume_unwind(e.into_panic()))
+    }
+
+    /// Tells if the task ended, e.g. since a ping failed.
+    pub fn is_finished(&self) -> bool{
+        self.handle.is_finished()
+    }
+}
@@ -59,19 +59,64 @@
 //! 
 //! [`Linux Kernel Documentation`]: https://www.kernel.org/doc/html/latest/watchdog/watchdog-api.html
 
-use log::{error, warn, info, trace};
-use std::fs::{File, OpenOptions};
+use log::{error, warn, info, trace, log, Level, LevelFilter};
+use std::fs::{self, File};
+#[cfg(target_os = "linux")]
+use std::fs::OpenOptions;
 use std::io::{self, Write};
 use std::fmt;
-use libc::c_int;
+use std::str::FromStr;
+use std::convert::Infallible;
+use std::path::{Path, PathBuf};
+use std::ffi::c_int;
 use std::thread;
 use std::thread::JoinHandle;
-use std::time::Duration;
-use std::sync::{Arc, Mutex, mpsc::Sender, mpsc::channel, mpsc::RecvTimeoutError};
+use std::time::{Duration, Instant};
+use std::sync::{Arc, Mutex, MutexGuard, PoisonError, mpsc::Sender, mpsc::channel, mpsc::RecvTimeoutError, mpsc::TryRecvError};
+use std::sync::atomic::{AtomicI32, AtomicUsize, Ordering};
 #[cfg(unix)]
-use std::os::unix::io::AsRawFd;
-use nix::errno::Errno;
+use std::os::unix::io::{AsFd, AsRawFd, BorrowedFd, FromRawFd, RawFd};
+#[cfg(target_os = "linux")]
+use std::os::unix::fs::OpenOptionsExt;
+use crate::errno::Errno;
 use crate::ioctl::*;
+use crate::sysfs;
+use crate::error::WatchdogError;
+use crate::shared::{KeepAliveGuard, SharedWatchdog, WatchdogWorker, WorkerStats};
+
+/// Number of consecutive pings not registered by the driver, after which the automatic keep alive thread reports an error.
+const MAX_UNREGISTERED_PINGS: u32 = 3;
+
+/// Number of consecutive keep alive errors after which the automatic keep alive thread gives up, unless configured otherwise.
+const DEFAULT_MAX_KEEPALIVE_ERRORS: u32 = 10;
+
+/// Boot status flags reporting a reset cause, considered by [`Watchdog::boot_reason()`].
+const BOOT_REASON_FLAGS: [OptionFlags; 7] = [OptionFlags::Overheat, OptionFlags::FanFault, OptionFlags::Extern1, OptionFlags::Extern2, 
+                                             OptionFlags::PowerUnder, OptionFlags::CardReset, OptionFlags::PowerOver];
+
+/// Bytes written by the write-based keep alive, unless configured otherwise.
+pub(crate) const DEFAULT_KEEPALIVE_WRITE_BYTES: &[u8] = b"1";
+
+/// Flags that are meaningful in the status and boot status bitmasks, as documented by the Linux Kernel watchdog API.
+/// The other options only describe a capability.
+const STATUS_FLAGS: [OptionFlags; 8] = [OptionFlags::Overheat, OptionFlags::FanFault, OptionFlags::Extern1, OptionFlags::Extern2, 
+                                        OptionFlags::PowerUnder, OptionFlags::CardReset, OptionFlags::PowerOver, OptionFlags::KeepalivePing];
+
+/// Status flags reporting a fault, considered by [`Watchdog::is_healthy()`].
+const FAULT_FLAGS: [OptionFlags; 4] = [OptionFlags::Overheat, OptionFlags::FanFault, OptionFlags::PowerUnder, OptionFlags::PowerOver];
+
+/// Interval between two pings of the automatic keep alive thread, unless configured otherwise.
+const DEFAULT_KEEP_ALIVE_INTERVAL: Duration = Duration::from_secs(1);
+
+/// Value of the cached timeout when unknown.
+const TIMEOUT_UNKNOWN: i32 = -1;
+
+/// Level of the messages logged when a watchdog is opened or closed, as a [`LevelFilter`] discriminant.
+/// See [`set_lifecycle_log_level()`].
+static LIFECYCLE_LOG_LEVEL: AtomicUsize = AtomicUsize::new(LevelFilter::Info as usize);
+
+/// Identity reported by the software watchdog driver (softdog).
+const SOFTDOG_IDENTITY: &str = "Software Watchdog";
 
 /// List of all available options that can be supported by a watchdog driver.
 /// 
@@ -82,6 +127,8 @@ use crate::ioctl::*;
 ///
 /// All options and their related values have been obtained from the Linux Kernel headers: 
 ///  - include/uapi/linux/watchdog.h in struct watchdog_info.options
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub enum OptionFlags{
     /// Reset due to CPU overheat
     Overheat,       
@@ -109,22 +156,105 @@ pub enum OptionFlags{
     KeepalivePing,  
 }
 
+/// Bit of each option, in the same order as the declaration of [`OptionFlags`], 
+/// so that the variants can be used as indexes.
+const OPTION_FLAG_BITS: [(OptionFlags, u32); 12] = [
+    (OptionFlags::Overheat,      0x0001),
+    (OptionFlags::FanFault,      0x0002),
+    (OptionFlags::Extern1,       0x0004),
+    (OptionFlags::Extern2,       0x0008),
+    (OptionFlags::PowerUnder,    0x0010),
+    (OptionFlags::CardReset,     0x0020),
+    (OptionFlags::PowerOver,     0x0040),
+    (OptionFlags::SetTimeout,    0x0080),
+    (OptionFlags::MagicClose,    0x0100),
+    (OptionFlags::PreTimeout,    0x0200),
+    (OptionFlags::AlarmOnly,     0x0400),
+    (OptionFlags::KeepalivePing, 0x8000),
+];
+
+/// All the options, in the order of declaration, taken from [`OPTION_FLAG_BITS`].
+///
+/// This also checks at compile time that the table follows the declaration order, which the indexing relies on.
+const ALL_OPTION_FLAGS: [OptionFlags; 12] = {
+    let mut all = [OptionFlags::Overheat; 12];
+    let mut i = 0;
+    while i < OPTION_FLAG_BITS.len(){
+        assert!(OPTION_FLAG_BITS[i].0 as usize == i, "OPTION_FLAG_BITS is not in the order of declaration");
+        all[i] = OPTION_FLAG_BITS[i].0;
+        i += 1;
+    }
+    all
+};
+
 impl OptionFlags{
-    fn value(&self) -> u32{
-        match self{
-            Self::Overheat        => 0x0001,
-            Self::FanFault        => 0x0002,   
-            Self::Extern1         => 0x0004,   
-            Self::Extern2         => 0x0008,   
-            Self::PowerUnder      => 0x0010,   
-            Self::CardReset       => 0x0020,   
-            Self::PowerOver       => 0x0040,   
-            Self::SetTimeout      => 0x0080,   
-            Self::MagicClose      => 0x0100,
-            Self::PreTimeout      => 0x0200,
-            Self::AlarmOnly       => 0x0400,
-            Self::KeepalivePing   => 0x8000,
-        }
+    pub(crate) fn value(&self) -> u32{
+        OPTION_FLAG_BITS[*self as usize].1
+    }
+
+    /// Returns all the options, in the order of declaration.
+    ///
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::OptionFlags;
+    /// 
+    /// for option in OptionFlags::all(){
+    ///     println!("{}", option);
+    /// }
+    /// ```
+    pub fn all() -> &'static [OptionFlags]{
+        &ALL_OPTION_FLAGS
+    }
+
+    /// Iterates over all the options, in the order of declaration.
+    ///
+    /// This is the same as iterating over [`all()`](Self::all), by value.
+    ///
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::OptionFlags;
+    /// 
+    /// assert_eq!(OptionFlags::variants().count(), OptionFlags::all().len());
+    /// ```
+    pub fn variants() -> impl Iterator<Item = OptionFlags>{
+        OPTION_FLAG_BITS.iter().map(|(option, _)| *option)
+    }
+
+    /// Returns the options that are meaningful in the status and boot status bitmasks.
+    ///
+    /// Only these flags can be queried with [`Watchdog::get_status()`] and [`Watchdog::get_boot_status()`]:
+    /// the other options (e.g. [`OptionFlags::SetTimeout`]) only describe a capability, so their status bits are meaningless.
+    /// Use [`all()`](Self::all) with [`Watchdog::is_option_supported()`] instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::OptionFlags;
+    /// 
+    /// assert!(OptionFlags::status_flags().contains(&OptionFlags::Overheat));
+    /// assert!(!OptionFlags::status_flags().contains(&OptionFlags::MagicClose));
+    /// ```
+    pub fn status_flags() -> &'static [OptionFlags]{
+        &STATUS_FLAGS
+    }
+
+    /// Returns the options whose bits are set in the bitmask, in one pass.
+    /// 
+    /// The bitmask can be either the one of the supported options, or the one returned by the status queries
+    /// (see [`Watchdog::get_status()`] and [`Watchdog::get_boot_status()`]). Unknown bits are ignored.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::OptionFlags;
+    /// 
+    /// assert_eq!(OptionFlags::from_bitmask(0x8180), 
+    ///            vec![OptionFlags::SetTimeout, OptionFlags::MagicClose, OptionFlags::KeepalivePing]);
+    /// ```
+    pub fn from_bitmask(bitmask: u32) -> Vec<OptionFlags>{
+        OptionSet::from_bits(bitmask).iter().collect()
     }
 }
 
@@ -147,7 +277,144 @@ impl fmt::Display for OptionFlags {
     }
 }
 
+/// Parses the name of an option, as printed by [`Display`](fmt::Display) (e.g. "MagicClose").
+///
+/// The comparison is case-insensitive, so that names can come from configuration files and command line arguments.
+///
+/// # Examples
+/// 
+/// ```rust
+/// use watchdog_device::OptionFlags;
+/// 
+/// assert_eq!("magicclose".parse(), Ok(OptionFlags::MagicClose));
+/// assert!("Unknown".parse::<OptionFlags>().is_err());
+/// ```
+impl FromStr for OptionFlags {
+    type Err = ParseOptionFlagsError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let name = s.trim();
+        Self::all().iter()
+            .find(|option| option.to_string().eq_ignore_ascii_case(name))
+            .copied()
+            .ok_or_else(|| ParseOptionFlagsError{name: name.to_string()})
+    }
+}
+
+/// Error returned when parsing an unknown [`OptionFlags`] name.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ParseOptionFlagsError{
+    name: String,
+}
+
+impl fmt::Display for ParseOptionFlagsError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "unknown watchdog option '{}'", self.name)
+    }
+}
+
+impl std::error::Error for ParseOptionFlagsError {}
+
+/// Set of options, e.g. the ones supported by a watchdog (see [`Watchdog::supported_options()`]).
+///
+/// The set is backed by the same bitmask used by the kernel, so that checking many options
+/// costs a single query.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::{OptionFlags, OptionSet};
+///
+/// let options = OptionSet::from_bits(0x8180);
+/// assert!(options.contains(OptionFlags::MagicClose));
+/// assert!(!options.contains(OptionFlags::Overheat));
+/// assert_eq!(options.to_string(), "SetTimeout, MagicClose, KeepalivePing");
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct OptionSet(u32);
+
+impl OptionSet {
+    /// Creates a set from a bitmask, as returned by the kernel. 
+    /// Unknown bits are kept, but only reported by [`bits()`](Self::bits) and [`unknown_bits()`](Self::unknown_bits).
+    pub fn from_bits(bits: u32) -> Self {
+        Self(bits)
+    }
+
+    /// Returns the underlying bitmask.
+    pub fn bits(&self) -> u32 {
+        self.0
+    }
+
+    /// Returns the bits of the bitmask that do not correspond to any [`OptionFlags`], e.g. options added by newer kernels.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::OptionSet;
+    ///
+    /// assert_eq!(OptionSet::from_bits(0x8180).unknown_bits(), 0);
+    /// assert_eq!(OptionSet::from_bits(0x10080).unknown_bits(), 0x10000);
+    /// ```
+    pub fn unknown_bits(&self) -> u32 {
+        let known = OPTION_FLAG_BITS.iter().fold(0, |known, (_, bit)| known | bit);
+        self.0 & !known
+    }
+
+    /// Tells if the option is in the set.
+    pub fn contains(&self, option: OptionFlags) -> bool {
+        (self.0 & option.value()) != 0
+    }
+
+    /// Tells if the set contains no known option.
+    pub fn is_empty(&self) -> bool {
+        self.iter().next().is_none()
+    }
+
+    /// Returns the options in the set, in the order of declaration of [`OptionFlags`].
+    pub fn iter(&self) -> impl Iterator<Item = OptionFlags> {
+        let bits = self.0;
+        OPTION_FLAG_BITS.iter()
+            .filter(move |(_, bit)| bits & bit != 0)
+            .map(|(option, _)| *option)
+    }
+}
+
+/// Serialized as the list of the option names (e.g. `["SetTimeout", "MagicClose"]`), 
+/// so that the output is self-documenting. Unknown bits are not serialized.
+///
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl serde::Serialize for OptionSet {
+    fn serialize<S: serde::Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(self.iter())
+    }
+}
+
+/// Deserialized from the list of the option names, as serialized.
+///
+/// Requires the `serde` feature.
+#[cfg(feature = "serde")]
+impl<'de> serde::Deserialize<'de> for OptionSet {
+    fn deserialize<D: serde::Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let options = Vec::<OptionFlags>::deserialize(deserializer)?;
+        Ok(Self(options.iter().fold(0, |bits, option| bits | option.value())))
+    }
+}
+
+impl fmt::Display for OptionSet {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        for (i, option) in self.iter().enumerate() {
+            if i > 0 {
+                write!(f, ", ")?;
+            }
+            write!(f, "{}", option)?;
+        }
+        Ok(())
+    }
+}
+
 /// The following are all the flags that can be set by using [`Watchdog::set_option()`](crate::watchdog_device::Watchdog::set_option).
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 pub enum SetOptionFlags{
     /// Turn off the watchdog timer
     DisableCard,     
@@ -177,16 +444,189 @@ impl fmt::Display for SetOptionFlags {
     }
 }
 
-enum BitmaskQueryType{
+/// The ways the watchdog can be pinged by [`Watchdog::keep_alive()`](crate::watchdog_device::Watchdog::keep_alive).
+///
+/// See [`WatchdogBuilder::keepalive_method()`](crate::builder::WatchdogBuilder::keepalive_method) to choose one.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum KeepAliveMethod{
+    /// Issue the `WDIOC_KEEPALIVE` ioctl (default)
+    #[default]
+    Ioctl,
+    /// Write a byte (other than the magic close character 'V') to the device file
+    Write,
+}
+
+impl fmt::Display for KeepAliveMethod {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Ioctl => write!(f, "Ioctl"),
+            Self::Write => write!(f, "Write"),
+       }
+    }
+}
+
+/// What happens when a [`Watchdog`] is dropped, see [`Watchdog::set_on_drop()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum OnDrop{
+    /// Just close the device, leaving the decision to the kernel (default): the watchdog is stopped
+    /// if it was magic closed, or if the driver does not support [`OptionFlags::MagicClose`], unless 'nowayout' is set.
+    #[default]
+    LeaveToKernel,
+    /// Magic close the watchdog before closing it, if supported, so that the watchdog is stopped even if 
+    /// the instance is dropped unexpectedly (e.g. during a panic).
+    MagicClose,
+    /// Keep the watchdog armed after the close, if possible: a pending [`Watchdog::magic_close()`] is cancelled,
+    /// so that only an explicit [`Watchdog::into_magic_close()`] stops it.
+    /// Drivers not supporting [`OptionFlags::MagicClose`] stop the watchdog on close regardless.
+    KeepArmed,
+}
+
+/// The pretimeout governors, deciding what happens when the pretimeout expires.
+///
+/// See [`Watchdog::available_pretimeout_governors()`](crate::watchdog_device::Watchdog::available_pretimeout_governors).
+/// The governors provided by the kernel are modeled explicitly, while any other one is kept by name.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum PretimeoutGovernor{
+    /// Only log the event
+    Noop,
+    /// Trigger a kernel panic
+    Panic,
+    /// Any other governor (e.g. provided by a vendor)
+    Other(String),
+}
+
+impl fmt::Display for PretimeoutGovernor {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            Self::Noop => write!(f, "noop"),
+            Self::Panic => write!(f, "panic"),
+            Self::Other(name) => write!(f, "{}", name),
+       }
+    }
+}
+
+impl From<&str> for PretimeoutGovernor {
+    fn from(name: &str) -> Self {
+        match name.trim() {
+            "noop" => Self::Noop,
+            "panic" => Self::Panic,
+            other => Self::Other(other.to_string()),
+        }
+    }
+}
+
+impl FromStr for PretimeoutGovernor {
+    type Err = Infallible;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(Self::from(s))
+    }
+}
+
+/// Outcome of [`Watchdog::set_timeout_checked()`], telling whether the driver applied the requested timeout as is.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SetTimeoutOutcome{
+    /// The driver applied exactly the requested timeout (in seconds).
+    Exact(i32),
+    /// The driver applied a different timeout, due to the limitations of the hardware.
+    Adjusted{
+        /// Requested timeout (in seconds).
+        requested: i32,
+        /// Timeout applied by the driver (in seconds).
+        actual: i32,
+    },
+}
+
+impl SetTimeoutOutcome {
+    /// Returns the timeout applied by the driver, in seconds.
+    pub fn actual(&self) -> i32 {
+        match *self {
+            Self::Exact(timeout) => timeout,
+            Self::Adjusted{actual, ..} => actual,
+        }
+    }
+
+    /// Tells if the driver applied exactly the requested timeout.
+    pub fn is_exact(&self) -> bool {
+        matches!(self, Self::Exact(_))
+    }
+}
+
+impl fmt::Display for SetTimeoutOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            Self::Exact(timeout) => write!(f, "{} secs", timeout),
+            Self::Adjusted{requested, actual} => write!(f, "requested {} secs, the driver applied {} secs", requested, actual),
+       }
+    }
+}
+
+/// Reason why the automatic keep alive thread ended, returned when joining it.
+///
+/// See [`Watchdog::start_automatic_keep_alive()`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum KeepAliveExit {
+    /// The thread was asked to stop, e.g. by [`Watchdog::magic_close()`] or [`Watchdog::stop_automatic_keep_alive()`].
+    Requested,
+    /// The thread gave up after the given number of consecutive keep alive errors:
+    /// the watchdog is no longer pinged, so the system is likely to be reset.
+    MaxErrors(u32),
+}
+
+/// How long the automatic keep alive thread waits between two pings.
+enum KeepAliveInterval{
+    Fixed(Duration),
+    /// Half of the time left, but at least 'min'.
+    Adaptive{min: Duration},
+    /// Half of the timeout, but at least one second. Recomputed when the timeout changes.
+    HalfTimeout,
+}
+
+pub(crate) enum BitmaskQueryType{
     GetStatus,
     GetBootStatus,
 }
 
-enum IntGetterType{
-    GetTimeout,
-    GetPreTimeout,
-    GetTimeLeft,
-    GetTemp,
+/// Capabilities of a watchdog, as reported by the `WDIOC_GETSUPPORT` ioctl.
+///
+/// With the `serde` feature, it can be serialized (e.g. to JSON), with the options as a list of names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct WatchdogInfo {
+    /// Options supported by the driver
+    pub options: OptionSet,
+    /// The firmware version of the card, if available
+    pub firmware_version: u32,
+    /// A string identifying the watchdog driver
+    pub identity: String,
+}
+
+impl WatchdogInfo {
+    pub(crate) fn from_raw(wd_info: &watchdog_info) -> Self {
+        WatchdogInfo{
+            options: OptionSet::from_bits(wd_info.options),
+            firmware_version: wd_info.firmware_version,
+            identity: identity_to_string(&wd_info.identity),
+        }
+    }
+
+    /// Tells if an option is supported.
+    pub fn is_option_supported(&self, option: &OptionFlags) -> bool {
+        self.options.contains(*option)
+    }
+
+    /// Returns the options bitmask as reported by the driver, including the bits unknown to this crate
+    /// (see [`OptionSet::unknown_bits()`]).
+    pub fn options_raw(&self) -> u32 {
+        self.options.bits()
+    }
+}
+
+pub(crate) enum IntGetterType{
+    Timeout,
+    PreTimeout,
+    TimeLeft,
+    Temp,
 }
 
 /// Structure representing the watchdog.
@@ -195,10 +635,48 @@ enum IntGetterType{
 /// the watchdog activates and needs to be pinged to avoid a system reset.
 pub struct Watchdog{
     /// File that activates the watchdog when opened.
-    file: File,
+    pub(crate) file: File,
+    /// Path of the file, with any symlink resolved.
+    path: PathBuf,
     /// Message passing utility used to tell the 'automatic keepalive' thread when to exit.
     /// This is used only when calling [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive), hence the 'Option'.
     msg_sender: Option<Sender<()>>,
+    /// How [`keep_alive()`](Self::keep_alive) pings the watchdog.
+    pub(crate) keepalive_method: KeepAliveMethod,
+    /// Whether [`keep_alive()`](Self::keep_alive) can switch to the write method if the ioctl is not supported.
+    pub(crate) keepalive_fallback: bool,
+    /// Capabilities (options, firmware version, identity), read once upon activation 
+    /// (see [`refresh_capabilities()`](Self::refresh_capabilities)).
+    capabilities: Option<watchdog_info>,
+    /// Boot status bitmask, read once upon activation (see [`get_boot_status()`](Self::get_boot_status)).
+    boot_status: Option<u32>,
+    /// Bytes written by the write-based keep alive (see [`KeepAliveMethod::Write`]). Never contains 'V'.
+    pub(crate) keepalive_write_bytes: Vec<u8>,
+    /// Whether [`get_driver_identity()`](Self::get_driver_identity) reads sysfs before issuing the ioctl.
+    pub(crate) identity_from_sysfs: bool,
+    /// Only every Nth successful keep alive is logged (0 means never).
+    pub(crate) keepalive_log_every: u64,
+    /// Number of successful keep alive pings, also used for logging. Saturating.
+    keep_alive_success_count: u64,
+    /// Number of failed keep alive pings. Saturating.
+    keep_alive_failure_count: u64,
+    /// Number of consecutive keep alive errors after which the automatic keep alive thread gives up (at least 1).
+    pub(crate) max_keepalive_errors: u32,
+    /// Whether the magic close is armed: set by [`magic_close()`](Self::magic_close), and cleared by any later write.
+    magic_closed: bool,
+    /// Time of the activation.
+    activated_at: Instant,
+    /// Time of the last successful keep alive, if any.
+    last_keep_alive: Option<Instant>,
+    /// A warning is logged if a ping comes later than the timeout minus this margin.
+    warn_margin: Option<Duration>,
+    /// Whether the last successful ping came later than the timeout minus the warn margin.
+    pub(crate) last_keep_alive_late: bool,
+    /// Timeout read upon activation and updated by [`set_timeout()`](Self::set_timeout), 
+    /// or [`TIMEOUT_UNKNOWN`]. Atomic, since the setter only borrows the instance.
+    cached_timeout: AtomicI32,
+    /// What to do when the instance is dropped.
+    on_drop: OnDrop,
 }
 
 impl Watchdog {
@@ -216,7 +694,10 @@ impl Watchdog {
     /// Once the watchdog is active, an alternative way to keep the system alive is to call 
     /// [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive) just once.
     /// See the documentation of each method for more information.
-    pub fn new() -> Result<Self, io::Error>{
+    ///
+    /// To also configure the timeout and the pretimeout upon activation, 
+    /// [`WatchdogBuilder`](crate::builder::WatchdogBuilder) is the recommended entry point.
+    pub fn new() -> Result<Self, WatchdogError>{
         Self::new_instance(None)
     }
 
@@ -227,128 +708,963 @@ impl Watchdog {
     /// The ID passed as parameter indicates the number suffix of the watchdog file.
     /// As with [`new()`](Self::new), The creation of the instance causes the activation of the watchdog.
     /// See [`new()`](Self::new) for more information.
-    pub fn new_by_id(id: u8) -> Result<Self, io::Error>{
+    pub fn new_by_id(id: u8) -> Result<Self, WatchdogError>{
         Self::new_instance(Some(id))
     }
+
+    /// Instantiates the watchdog at the given path.
+    ///
+    /// Unlike [`new()`](Self::new) and [`new_by_id()`](Self::new_by_id), the device file can be anywhere,
+    /// e.g. in a custom devtmpfs mount, a path bind-mounted into a container, or a symlink.
+    /// The file is opened in the same way, and the same errors are returned.
+    /// As with [`new()`](Self::new), the creation of the instance causes the activation of the watchdog.
+    /// See [`new()`](Self::new) for more information.
+    pub fn open_path<P: AsRef<Path>>(path: P) -> Result<Self, WatchdogError>{
+        Self::open_instance(path.as_ref())
+    }
     
-    fn new_instance(id: Option<u8>) -> Result<Self, io::Error>{
+    /// Returns the path of the device the watchdog is bound to.
+    ///
+    /// This is the path the watchdog was opened from, with any symlink resolved 
+    /// (e.g. '/dev/watchdog0' if '/dev/watchdog' is a symlink to it), so that multiple instances can be told apart.
+    /// No ioctl is issued.
+    pub fn path(&self) -> &Path{
+        &self.path
+    }
+
+    /// Reads the capabilities of the watchdog at the given path, and releases it right away.
+    ///
+    /// The device is opened, the `WDIOC_GETSUPPORT` ioctl is issued, and the watchdog is closed
+    /// with [`magic_close()`](Self::magic_close) if supported, so that tooling can enumerate capabilities
+    /// without taking on the keep alive duty.
+    /// Beware that opening the device still arms the watchdog for a brief moment:
+    /// if 'Magic Close' is not supported the driver stops it on close, but on drivers with 'nowayout' set
+    /// (see [`can_disable()`](Self::can_disable)) the watchdog stays armed even after this probe, and will reset the system
+    /// unless it is pinged again.
+    /// See [`inspect()`](crate::sysfs::inspect) for a way to read the capabilities from sysfs without opening the device.
+    pub fn probe_path<P: AsRef<Path>>(path: P) -> Result<WatchdogInfo, WatchdogError>{
+        let mut wd = Self::open_path(path)?;
+        let support = wd.get_support();
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap_or(false){
+            wd.magic_close()?;
+        }
+        Ok(WatchdogInfo::from_raw(&support?))
+    }
+
+    fn new_instance(id: Option<u8>) -> Result<Self, WatchdogError>{
         let mut path = String::from("/dev/watchdog");
         if let Some(id_val) = id {
             path.push_str(&id_val.to_string());
         }
-        let f = OpenOptions::new().write(true).open(&path)?;
-        warn!("Watchdog:{path} activated.");
-        Ok(Self{file: f, msg_sender: Option::None})
+        Self::open_path(path)
     }
 
-    /// Keeps the system alive.
+    /// Instantiates the first watchdog that can be activated.
     ///
-    /// The watchdog automatically triggers a system reset if not pinged for a preconfigured timeout 
-    /// (see [`get_timeout()`](Self::get_timeout) and [`get_time_left()`](Self::get_time_left)).
-    /// In order to prevent this, this method must be called periodically before the timeout expires.
-    pub fn keep_alive(&mut self) -> Result<(), Errno>{
-        let result;
-        // The following could also be achieved with: self.file.write(b"0");
-        unsafe{
-            result = ioctl_keepalive(self.file.as_raw_fd(), std::ptr::null_mut::<c_int>());
+    /// On some systems the watchdog ID depends on the order in which the drivers are probed at boot 
+    /// (e.g. '/dev/watchdog0' or '/dev/watchdog1'). Instead of hardcoding it, this tries all the 
+    /// watchdog devices present in '/dev' in order, starting from the default one ('/dev/watchdog'),
+    /// and returns the first one that was opened successfully. Devices that cannot be opened 
+    /// (e.g. because they are already in use) are skipped.
+    /// If no device can be opened, the returned error lists the failure of each attempt,
+    /// unless all of them failed with the same errno (e.g. EBUSY): in that case, that error is returned as is.
+    /// As with [`new()`](Self::new), the creation of the instance causes the activation of the watchdog.
+    pub fn open_first_available() -> Result<Self, WatchdogError>{
+        let mut failures = Vec::new();
+        let mut errnos = Vec::new();
+        for path in list_devices()?{
+            match Self::open_instance(&path){
+                Ok(wd) => {
+                    info!("Watchdog:{} chosen as the first available.", path.display());
+                    return Ok(wd);
+                },
+                Err(e) => {
+                    info!("Could not open {}, skipping it. error:{}", path.display(), e);
+                    failures.push(format!("{}: {}", path.display(), e));
+                    errnos.push(e.raw_os_error());
+                },
+            }
         }
-        match result{
-            Ok(_) => {
-                trace!("Keep alive.");
-                Ok(())
-            },
-            Err(e) => Err(e),
+        if failures.is_empty(){
+            return Err(io::Error::new(io::ErrorKind::NotFound, "no watchdog device found").into());
+        }
+        if let Some(errno) = errnos[0]{
+            if errnos.iter().all(|e| *e == Some(errno)){
+                return Err(io::Error::from_raw_os_error(errno).into());
+            }
         }
+        Err(io::Error::other(format!("no watchdog device could be opened ({})", failures.join(", "))).into())
     }
 
-    /// Starts automatically keeping the system alive.
-    /// 
-    /// In a normal operation, the user should periodically call [`keep_alive()`](Self::keep_alive) to prevent the watchdog from triggering a system reset.
-    /// When calling this, a separate thread is spawned that takes care of pinging the watchdog once every second.
-    /// 
-    /// The 'auto keep alive' thread is signaled to be closed as soon as the watchdog instance is released from memory. 
-    /// This means that without triggering the [`magic_close()`](Self::magic_close) feature, releasing the watchdog will still cause a system reset after the timeout period.
+    /// Instantiates the software watchdog.
     ///
-    /// **Disclaimer**: this feature should only be considered if the user is sure that their use case will not defeat the purpose of having a watchdog in the first place.
-    /// As an example, if the main thread malfunctions but the 'auto keep alive' thread is able to keep running, 
-    /// the watchdog will still be pinged normally and no reset will take place. 
-    /// This is clearly an undersirable behaviour, so particular caution must be taken when using this.
+    /// This is meant for development and testing: unlike [`new()`](Self::new), it refuses to activate real hardware,
+    /// protecting developers from accidentally running destructive tests on the physical watchdog of their machine.
+    /// The software watchdog is provided by the `softdog` kernel module (see the crate documentation).
+    ///
+    /// The device is searched in sysfs by its identity, so that no other watchdog is activated in the process.
+    /// If sysfs is not available, the default watchdog is opened and its identity is verified 
+    /// (see [`is_software_watchdog()`](Self::is_software_watchdog)): if it is not the software watchdog,
+    /// it is released with [`magic_close()`](Self::magic_close) if supported, and an error is returned.
     ///
-    /// # Panics
-    /// This method can panic in case the passed mutex is poisoned. 
-    /// The same also can happen inside the spawned thread.
-    /// 
     /// # Examples
-    /// 
+    ///
     /// ```rust
     /// use watchdog_device::Watchdog;
-    /// use nix::errno::Errno;
-    /// use std::sync::{Arc, Mutex};
-    /// 
-    /// # fn do_something(){}
+    ///
     /// # fn main() -> Result<(), std::io::Error> {
-    /// let mut wd = Watchdog::new()?;
-    /// let wd_mutex_arc: Arc<Mutex<Watchdog>> = Arc::new(Mutex::new(wd));
-    /// let handle = Watchdog::start_automatic_keep_alive(wd_mutex_arc.clone());
-    /// loop{
-    ///     do_something();
-    /// #   break;
-    /// }
-    /// # wd_mutex_arc.lock().expect("Error obtaining lock guard.").magic_close()?;
-    /// handle.join().expect("Error joining thread.");
+    /// let mut wd = Watchdog::new_softdog()?;
+    /// wd.keep_alive()?;
+    /// # wd.magic_close()?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn start_automatic_keep_alive(watchdog_mut_arc: Arc<Mutex<Self>>) -> JoinHandle<()>{
-        let (tx, rx) = channel::<()>();
-        watchdog_mut_arc.lock().expect("Couldn't lock the watchdog mutex to set the sender.").msg_sender = Some(tx);
-        let handle = thread::spawn(move || {
-            info!("Automatic keepalive thread started.");
-            let mut keepalive_error_counter = 0;
-            loop{
-                if let Err(e) = watchdog_mut_arc.lock().expect("Couldn't lock the watchdog mutex to keep alive.").keep_alive(){
-                    warn!("Keep alive error {}.", e);
-                    keepalive_error_counter += 1;
-                    if keepalive_error_counter >= 10{
-                        error!("Max number of consecutive keepalive errors reached. Closing thread...");
-                        break;
-                    }
-                }
-                else{
-                    keepalive_error_counter = 0;
-                }
-                // These two 'errors' are used as information, so it is not needed to send actual messages.
-                if let Err(e) =  rx.recv_timeout(Duration::from_secs(1)){
-                    if e == RecvTimeoutError::Timeout{ 
-                        trace!("timeout 1s...");
-                    }
-                    else{
-                        // The sender being dropped is an implicit signal that this thread must close.
-                        warn!("Sender was terminated. Closing 'auto keepalive' thread...");
-                        break;
-                    }
-                } // Ok() not used, since the two error types are the only information needed.
+    pub fn new_softdog() -> Result<Self, WatchdogError>{
+        let path = match sysfs::find_by_identity(SOFTDOG_IDENTITY){
+            Ok(Some(name)) => Path::new("/dev").join(name),
+            Ok(None) => return Err(io::Error::new(io::ErrorKind::NotFound, 
+                "software watchdog not found: load it with 'modprobe softdog'").into()),
+            Err(e) => {
+                info!("Couldn't search the software watchdog in sysfs:{}", e);
+                Path::new("/dev/watchdog").to_path_buf()
+            },
+        };
+        let mut wd = Self::open_instance(&path)?;
+        if !wd.is_software_watchdog()?{
+            if wd.is_option_supported(&OptionFlags::MagicClose)?{
+                wd.magic_close()?;
             }
-            info!("Automatic keepalive thread ended.");
-        });
-        handle
+            return Err(io::Error::new(io::ErrorKind::InvalidInput, 
+                format!("{} is not the software watchdog", path.display())).into());
+        }
+        Ok(wd)
     }
 
-    /// Returns the version of the firmware.
-    /// 
-    /// If available, this returns the firmware version of the card.
-    /// 
-    /// # Examples
-    /// 
-    /// ```rust
-    /// use watchdog_device::Watchdog;
-    /// use nix::errno::Errno;
-    /// use log::{info, error};
-    /// 
-    /// # fn main() -> Result<(), std::io::Error> {
-    /// let mut wd = Watchdog::new()?;
-    /// let result = wd.get_firmware_version();
-    /// match result{
+    pub(crate) fn open_instance(path: &Path) -> Result<Self, WatchdogError>{
+        Self::open_instance_with_flags(path, 0)
+    }
+
+    /// Fails with ENOSYS, since the watchdog API is only available on Linux.
+    ///
+    /// Opening the file anyway would only fake a watchdog, e.g. pinging a regular file.
+    #[cfg(not(target_os = "linux"))]
+    pub(crate) fn open_instance_with_flags(path: &Path, _custom_flags: c_int) -> Result<Self, WatchdogError>{
+        error!("Couldn't open watchdog:{}. The watchdog API is only available on Linux.", path.display());
+        Err(Errno::ENOSYS.into())
+    }
+
+    /// Opens the watchdog, OR-ing the given `O_*` flags into the ones used by default.
+    #[cfg(target_os = "linux")]
+    pub(crate) fn open_instance_with_flags(path: &Path, custom_flags: c_int) -> Result<Self, WatchdogError>{
+        // '/dev/watchdog' is often a symlink (e.g. to '/dev/watchdog0'): report the real device.
+        let real_path = fs::canonicalize(path).unwrap_or_else(|_| path.to_path_buf());
+        let device_name = if real_path == path{
+            path.display().to_string()
+        }
+        else{
+            format!("{} ({})", path.display(), real_path.display())
+        };
+        let f = match OpenOptions::new().write(true).custom_flags(custom_flags).open(&real_path){
+            Ok(f) => f,
+            Err(e) => {
+                // The error is returned as is, to preserve the errno.
+                error!("Couldn't open watchdog:{}. error:{}", device_name, e);
+                if e.raw_os_error() == Some(libc::EBUSY){
+                    info!("The watchdog could be open through another node: e.g. '/dev/watchdog' and '/dev/watchdog0' \
+                        usually are the same watchdog (see resolve_device()).");
+                }
+                return Err(e.into());
+            },
+        };
+        log_lifecycle(format_args!("Watchdog:{} activated.", device_name));
+        Ok(Self::from_activated_file(f, real_path))
+    }
+
+    /// Wraps a watchdog that was already opened elsewhere.
+    ///
+    /// This is meant for deployments where the device is not opened by this library, e.g. a file descriptor 
+    /// inherited through socket activation, or opened by a wrapper before dropping privileges.
+    /// The file must have been opened for writing, so the watchdog is already active: the capabilities, 
+    /// the boot status and the timeout are read as [`new()`](Self::new) does.
+    ///
+    /// The path is resolved through '/proc/self/fd', if available, or set to `<unknown>` (see [`path()`](Self::path)).
+    pub fn from_file(file: File) -> Self{
+        let path = fs::read_link(format!("/proc/self/fd/{}", file.as_raw_fd()))
+            .unwrap_or_else(|_| PathBuf::from("<unknown>"));
+        log_lifecycle(format_args!("Watchdog:{} wrapped.", path.display()));
+        Self::from_activated_file(file, path)
+    }
+
+    /// Wraps a watchdog file descriptor that was already opened elsewhere, taking ownership of it.
+    ///
+    /// See [`from_file()`](Self::from_file).
+    ///
+    /// # Safety
+    ///
+    /// The file descriptor must be open and owned by the caller, which must not use nor close it afterwards,
+    /// as for [`FromRawFd::from_raw_fd()`](std::os::unix::io::FromRawFd::from_raw_fd).
+    #[cfg(unix)]
+    pub unsafe fn from_raw_fd(fd: RawFd) -> Self{
+        Self::from_file(File::from_raw_fd(fd))
+    }
+
+    /// Instantiates the watchdog on an already opened file, reading the state needed upon activation.
+    fn from_activated_file(file: File, path: PathBuf) -> Self{
+        let mut wd = Self::with_file(file, path);
+        if let Err(e) = wd.refresh_capabilities(){
+            trace!("Capabilities not available:{}", e);
+        }
+        if let Err(e) = wd.refresh_boot_status(){
+            trace!("Boot status not available:{}", e);
+        }
+        match wd.get_timeout(){
+            Ok(timeout) => wd.cached_timeout.store(timeout, Ordering::Relaxed),
+            Err(e) => trace!("Timeout not available:{}", e),
+        }
+        wd
+    }
+
+    fn with_file(file: File, path: PathBuf) -> Self{
+        Self{
+            file,
+            path,
+            msg_sender: Option::None,
+            keepalive_method: KeepAliveMethod::default(),
+            keepalive_fallback: true,
+            capabilities: Option::None,
+            boot_status: Option::None,
+            keepalive_write_bytes: DEFAULT_KEEPALIVE_WRITE_BYTES.to_vec(),
+            identity_from_sysfs: false,
+            keepalive_log_every: 1,
+            keep_alive_success_count: 0,
+            keep_alive_failure_count: 0,
+            max_keepalive_errors: DEFAULT_MAX_KEEPALIVE_ERRORS,
+            magic_closed: false,
+            activated_at: Instant::now(),
+            last_keep_alive: Option::None,
+            warn_margin: Option::None,
+            last_keep_alive_late: false,
+            cached_timeout: AtomicI32::new(TIMEOUT_UNKNOWN),
+            on_drop: OnDrop::default(),
+        }
+    }
+
+    /// Duplicates the watchdog instance.
+    ///
+    /// [`Watchdog`] cannot implement `Clone`, since it owns the file representing the driver.
+    /// This method provides an alternative: it returns a new instance operating on a duplicate
+    /// of the same file descriptor, which can be moved freely (e.g. to another thread).
+    ///
+    /// Both instances refer to the same hardware, so the following applies:
+    ///  - Pinging either of them keeps the system alive.
+    ///  - The watchdog is released only when all the duplicates are closed.
+    ///  - [`magic_close()`](Self::magic_close) can be called on any of them, and disarms the watchdog 
+    ///    once the last duplicate is closed. Note that a write-based ping (see [`KeepAliveMethod::Write`]) 
+    ///    after the magic close cancels it.
+    ///  - The duplicate does not control any automatic keep alive thread started on the original 
+    ///    instance (see [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive)).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use std::thread;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// let mut wd_dup = wd.duplicate()?;
+    /// thread::spawn(move || {
+    ///     wd_dup.keep_alive().unwrap();
+    /// }).join().expect("Error joining thread.");
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn duplicate(&self) -> Result<Self, WatchdogError>{
+        let f = self.file.try_clone()?;
+        trace!("Watchdog duplicated.");
+        let mut wd = Self::with_file(f, self.path.clone());
+        wd.keepalive_method = self.keepalive_method;
+        wd.keepalive_fallback = self.keepalive_fallback;
+        wd.capabilities = self.capabilities;
+        wd.boot_status = self.boot_status;
+        wd.keepalive_write_bytes = self.keepalive_write_bytes.clone();
+        wd.identity_from_sysfs = self.identity_from_sysfs;
+        wd.keepalive_log_every = self.keepalive_log_every;
+        wd.max_keepalive_errors = self.max_keepalive_errors;
+        wd.activated_at = self.activated_at;
+        wd.last_keep_alive = self.last_keep_alive;
+        wd.warn_margin = self.warn_margin;
+        wd.cached_timeout.store(self.cached_timeout.load(Ordering::Relaxed), Ordering::Relaxed);
+        Ok(wd)
+    }
+
+    /// Duplicates the watchdog instance, as [`File::try_clone()`] does for files.
+    ///
+    /// This is the same as [`duplicate()`](Self::duplicate), whose notes apply: e.g. one instance can be moved
+    /// to a keep alive thread, while the other one issues the status queries, without any mutex.
+    /// The clone does not control the automatic keep alive thread of this instance.
+    ///
+    /// Beware that the device is released when the last instance is closed, and what happens then depends on the driver 
+    /// and on the instance that wrote last: a magic close written through any instance, and not cancelled later, 
+    /// stops the watchdog.
+    pub fn try_clone(&self) -> Result<Self, WatchdogError>{
+        self.duplicate()
+    }
+
+    /// Keeps the system alive.
+    ///
+    /// The watchdog automatically triggers a system reset if not pinged for a preconfigured timeout 
+    /// (see [`get_timeout()`](Self::get_timeout) and [`get_time_left()`](Self::get_time_left)).
+    /// In order to prevent this, this method must be called periodically before the timeout expires.
+    ///
+    /// By default the watchdog is pinged with the `WDIOC_KEEPALIVE` ioctl; 
+    /// see [`KeepAliveMethod`] for the alternatives.
+    /// Some drivers do not implement the ioctl, and only support being pinged by writing to the device:
+    /// in that case, the failure (`ENOTTY`) is detected and the device is written to instead. 
+    /// Once such a write succeeds, [`KeepAliveMethod::Write`] is used from then on.
+    /// This fallback does not happen if the method was explicitly chosen with 
+    /// [`WatchdogBuilder::keepalive_method()`](crate::builder::WatchdogBuilder::keepalive_method).
+    ///
+    /// Each successful ping is logged at trace level, unless configured otherwise with 
+    /// [`WatchdogBuilder::keepalive_log_every()`](crate::builder::WatchdogBuilder::keepalive_log_every).
+    /// Errors are never logged here, since they are returned to the caller.
+    pub fn keep_alive(&mut self) -> Result<(), WatchdogError>{
+        Ok(self.keep_alive_errno()?)
+    }
+
+    /// Keeps the system alive, and returns the time left before the reset, in seconds.
+    ///
+    /// This issues two ioctls: the ping, and then [`get_time_left()`](Self::get_time_left), so that a supervisory loop
+    /// knows its margin right after a successful ping. If the driver does not support the time left, 
+    /// the configured timeout is returned instead, which is the margin right after the ping 
+    /// (the [cached](Self::cached_timeout) value, if known).
+    /// Use the plain [`keep_alive()`](Self::keep_alive) when the margin is not needed.
+    pub fn keep_alive_and_report(&mut self) -> Result<i32, WatchdogError>{
+        self.keep_alive_errno()?;
+        match Self::int_getter(self.file.as_raw_fd(), IntGetterType::TimeLeft){
+            Ok(time_left) => Ok(time_left),
+            Err(Errno::ENOTTY | Errno::EOPNOTSUPP) => match self.cached_timeout(){
+                Some(timeout) => Ok(timeout),
+                None => self.get_timeout(),
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Keeps the system alive by writing to the device, regardless of the configured [`KeepAliveMethod`].
+    ///
+    /// Writing any byte other than the magic close character 'V' pings the watchdog, as the `WDIOC_KEEPALIVE` ioctl does.
+    /// Some drivers only support this path reliably: this allows using it for a single ping, e.g. as a manual fallback,
+    /// without changing the method used by [`keep_alive()`](Self::keep_alive).
+    /// The bytes written are the configured ones (see 
+    /// [`WatchdogBuilder::keepalive_write_bytes()`](crate::builder::WatchdogBuilder::keepalive_write_bytes)).
+    ///
+    /// As any write, this cancels a pending [`magic_close()`](Self::magic_close). 
+    /// The ping is accounted as the ones of [`keep_alive()`](Self::keep_alive) (see [`ping_stats()`](Self::ping_stats)).
+    pub fn keep_alive_via_write(&mut self) -> Result<(), WatchdogError>{
+        let result = self.ping(KeepAliveMethod::Write);
+        Ok(self.record_keep_alive(result)?)
+    }
+
+    /// Implements [`keep_alive()`](Self::keep_alive), returning the errno as is for the automatic keep alive thread.
+    fn keep_alive_errno(&mut self) -> Result<(), Errno>{
+        let result = self.ping_with_fallback();
+        self.record_keep_alive(result)
+    }
+
+    /// Keeps the system alive for the automatic keep alive thread, verifying that the ping was registered by the driver.
+    ///
+    /// `is_registered` reads the [`OptionFlags::KeepalivePing`] status. After [`MAX_UNREGISTERED_PINGS`] consecutive 
+    /// unregistered pings, the ping fails with EIO. The bookkeeping (see [`ping_stats()`](Self::ping_stats)) only happens 
+    /// after the verification, so that such a ping is never accounted as successful.
+    fn keep_alive_verified<F>(&mut self, unregistered_ping_counter: &mut u32, is_registered: F) -> Result<(), Errno>
+    where F: FnOnce(&Self) -> Result<bool, WatchdogError> {
+        let mut result = self.ping_with_fallback();
+        if result.is_ok(){
+            if let Ok(false) = is_registered(self){
+                *unregistered_ping_counter += 1;
+                warn!("Keep alive ping not registered by the driver.");
+                if *unregistered_ping_counter >= MAX_UNREGISTERED_PINGS{
+                    error!("{} consecutive keep alive pings were not registered by the driver.", unregistered_ping_counter);
+                    // The watchdog is not being fed, even if the ioctl succeeds.
+                    result = Err(Errno::EIO);
+                }
+            }
+            else{
+                *unregistered_ping_counter = 0;
+            }
+        }
+        self.record_keep_alive(result)
+    }
+
+    /// Pings the watchdog with the configured method, falling back to writing if the ioctl is not supported.
+    fn ping_with_fallback(&mut self) -> Result<(), Errno>{
+        let mut result = self.ping(self.keepalive_method);
+        if self.keepalive_fallback && self.keepalive_method == KeepAliveMethod::Ioctl && result == Err(Errno::ENOTTY){
+            warn!("The driver doesn't support the keep alive ioctl. Falling back to writing to the device.");
+            result = self.ping(KeepAliveMethod::Write);
+            match result{
+                Ok(_) => {
+                    info!("The keep alive by writing to the device succeeded: using it from now on.");
+                    self.keepalive_method = KeepAliveMethod::Write;
+                },
+                // The ioctl is tried again on the next ping, since the write could have failed for other reasons.
+                Err(e) => error!("The keep alive by writing to the device failed too:{}", e),
+            }
+        }
+        result
+    }
+
+    /// Updates the bookkeeping after a ping, returning its result.
+    fn record_keep_alive(&mut self, result: Result<(), Errno>) -> Result<(), Errno>{
+        match result{
+            Ok(_) => {
+                self.last_keep_alive_late = self.check_warn_margin();
+                self.last_keep_alive = Some(Instant::now());
+                self.keep_alive_success_count = self.keep_alive_success_count.saturating_add(1);
+                // No remainder if 'keepalive_log_every' is zero, which disables the logging.
+                if self.keep_alive_success_count.checked_rem(self.keepalive_log_every) == Some(0){
+                    trace!("Keep alive #{}.", self.keep_alive_success_count);
+                }
+                Ok(())
+            },
+            Err(e) => {
+                self.keep_alive_failure_count = self.keep_alive_failure_count.saturating_add(1);
+                Err(e)
+            },
+        }
+    }
+
+    /// Returns the number of successful and failed keep alive pings of this instance, in this order.
+    ///
+    /// All the pings are counted, including the ones of the automatic keep alive thread 
+    /// (see [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive)), so that they can be exposed as metrics.
+    /// The counters saturate at `u64::MAX`, and are not copied by [`duplicate()`](Self::duplicate).
+    pub fn ping_stats(&self) -> (u64, u64){
+        (self.keep_alive_success_count, self.keep_alive_failure_count)
+    }
+
+    /// Sets a margin to warn about pings that came dangerously close to the deadline.
+    ///
+    /// Once set, whenever [`keep_alive()`](Self::keep_alive) succeeds more than `timeout - margin` after the previous 
+    /// successful ping (or the activation), a warning is logged: the application is feeding the watchdog too slowly, 
+    /// even if no reset happened yet. The timeout is the [cached one](Self::cached_timeout): 
+    /// if it is unknown, nothing is logged. By default no margin is set.
+    pub fn set_warn_margin(&mut self, margin: Duration){
+        self.warn_margin = Some(margin);
+    }
+
+    /// Sets the number of consecutive keep alive errors after which the automatic keep alive thread gives up.
+    ///
+    /// The default is 10. A higher value tolerates a flaky device, a lower one fails fast.
+    /// The value is read when the thread starts, and 0 is treated as 1.
+    /// See also [`WatchdogBuilder::max_keepalive_errors()`](crate::builder::WatchdogBuilder::max_keepalive_errors).
+    pub fn set_max_keepalive_errors(&mut self, max_errors: u32){
+        self.max_keepalive_errors = max_errors.max(1);
+    }
+
+    /// Sets what happens when the instance is dropped.
+    ///
+    /// By default ([`OnDrop::LeaveToKernel`]) the device is just closed, and the kernel decides whether to stop the watchdog.
+    /// See [`OnDrop`] for the alternatives.
+    /// The setting is not copied by [`duplicate()`](Self::duplicate), since every duplicate is closed on its own.
+    pub fn set_on_drop(&mut self, on_drop: OnDrop){
+        self.on_drop = on_drop;
+    }
+
+    /// Returns the time elapsed since the last successful [`keep_alive()`](Self::keep_alive) of this instance.
+    ///
+    /// Failed pings are not taken into account. This helps diagnosing near-misses: a value approaching the timeout
+    /// means that the application is feeding the watchdog too slowly (see also [`set_warn_margin()`](Self::set_warn_margin)).
+    /// No ioctl is issued.
+    ///
+    /// Returns None if no ping succeeded yet.
+    pub fn time_since_last_keep_alive(&self) -> Option<Duration>{
+        self.last_keep_alive.map(|last| last.elapsed())
+    }
+
+    /// Warns if the time since the last ping (or the activation) exceeded the timeout minus the warn margin, 
+    /// returning whether it did. Without a warn margin or a known timeout, no ping is late.
+    fn check_warn_margin(&self) -> bool{
+        if let (Some(margin), Some(timeout)) = (self.warn_margin, self.cached_timeout()){
+            let elapsed = self.last_keep_alive.unwrap_or(self.activated_at).elapsed();
+            let threshold = Duration::from_secs(timeout.max(0) as u64).saturating_sub(margin);
+            if elapsed > threshold{
+                warn!("Keep alive {:?} after the previous one, close to the timeout of {} secs.", elapsed, timeout);
+                return true;
+            }
+        }
+        false
+    }
+
+    fn ping(&mut self, method: KeepAliveMethod) -> Result<(), Errno>{
+        let result = match method{
+            KeepAliveMethod::Ioctl => unsafe{
+                ioctl_keepalive(self.file.as_raw_fd(), std::ptr::null_mut::<c_int>())
+            },
+            KeepAliveMethod::Write => {
+                // Any write cancels the magic close.
+                self.magic_closed = false;
+                write_keepalive(&mut self.file, &self.keepalive_write_bytes)
+                    .map(|_| 0)
+                    // Not pinged, but no errno reported by the driver: EIO.
+                    .map_err(|e| e.errno().unwrap_or(Errno::EIO))
+            },
+        };
+        result.map(|_| ())
+    }
+
+    /// Starts automatically keeping the system alive.
+    /// 
+    /// In a normal operation, the user should periodically call [`keep_alive()`](Self::keep_alive) to prevent the watchdog from triggering a system reset.
+    /// When calling this, a separate thread is spawned that takes care of pinging the watchdog once every second.
+    /// 
+    /// If the driver supports [`OptionFlags::KeepalivePing`], the thread also verifies through [`get_status()`](Self::get_status)
+    /// that each ping was actually registered: after a few consecutive unregistered pings, an error is logged
+    /// and the pings are counted as failed (also in [`ping_stats()`](Self::ping_stats)), even though the ioctl itself succeeded.
+    /// 
+    /// The 'auto keep alive' thread is signaled to be closed by [`magic_close()`](Self::magic_close). 
+    /// The signal is checked while holding the lock, right before each ping, so no ping is sent once the magic close took place.
+    /// Note that the thread owns a reference to the shared watchdog: as long as it runs, the watchdog is never released from memory, 
+    /// even if all the other `Arc` references are dropped. The watchdog is only closed once all the references 
+    /// (including the one of the thread) are gone. See [`SharedWatchdog::shutdown()`](crate::shared::SharedWatchdog::shutdown) 
+    /// to stop the thread and get the watchdog back.
+    /// As usual, without triggering the [`magic_close()`](Self::magic_close) feature, releasing the watchdog will still cause a system reset after the timeout period.
+    ///
+    /// **Disclaimer**: this feature should only be considered if the user is sure that their use case will not defeat the purpose of having a watchdog in the first place.
+    /// As an example, if the main thread malfunctions but the 'auto keep alive' thread is able to keep running, 
+    /// the watchdog will still be pinged normally and no reset will take place. 
+    /// This is clearly an undersirable behaviour, so particular caution must be taken when using this.
+    ///
+    /// Joining the returned handle tells why the thread ended (see [`KeepAliveExit`]): 
+    /// a requested stop, or too many consecutive keep alive errors.
+    ///
+    /// If another thread panics while holding the lock, the poisoned mutex is recovered and the pings go on, 
+    /// logging an error: a dead keep alive thread would reset the system.
+    /// See [`SharedWatchdog::lock_or_recover()`](crate::shared::SharedWatchdog::lock_or_recover) for the implications.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use nix::errno::Errno;
+    /// use std::sync::{Arc, Mutex};
+    /// 
+    /// # fn do_something(){}
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// let wd_mutex_arc: Arc<Mutex<Watchdog>> = Arc::new(Mutex::new(wd));
+    /// let handle = Watchdog::start_automatic_keep_alive(wd_mutex_arc.clone());
+    /// loop{
+    ///     do_something();
+    /// #   break;
+    /// }
+    /// # wd_mutex_arc.lock().expect("Error obtaining lock guard.").magic_close()?;
+    /// handle.join().expect("Error joining thread.");
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn start_automatic_keep_alive(watchdog_mut_arc: Arc<Mutex<Self>>) -> JoinHandle<KeepAliveExit>{
+        Self::start_automatic_keep_alive_with_interval(watchdog_mut_arc, DEFAULT_KEEP_ALIVE_INTERVAL)
+    }
+
+    /// Starts automatically keeping the system alive, pinging once every `interval`.
+    ///
+    /// This behaves like [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive), 
+    /// which pings once every second, but with a custom interval.
+    /// The interval should be comfortably shorter than the configured timeout (see [`get_timeout()`](Self::get_timeout)), 
+    /// to leave room for scheduling delays: otherwise the system could be reset even though the thread is running.
+    /// A zero interval is rejected: an error is logged and the thread pings once every second instead.
+    /// If the timeout is later changed with [`set_timeout()`](Self::set_timeout) to a value not longer than the interval,
+    /// an error is logged and the thread pings at half of the new timeout instead.
+    pub fn start_automatic_keep_alive_with_interval(watchdog_mut_arc: Arc<Mutex<Self>>, interval: Duration) -> JoinHandle<KeepAliveExit>{
+        Self::spawn_keep_alive_thread(watchdog_mut_arc, KeepAliveInterval::Fixed(interval), |_| {})
+    }
+
+    /// Starts automatically keeping the system alive, pinging at half of the configured timeout.
+    ///
+    /// This behaves like [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive), 
+    /// but the watchdog is pinged at half of the configured timeout (see [`cached_timeout()`](Self::cached_timeout)), 
+    /// never more often than once every second.
+    /// This is the common heuristic of watchdog daemons, which avoids both pinging too often and cutting it too close.
+    /// The chosen interval is logged at info level. If the timeout can't be read, a warning is logged 
+    /// and the thread pings once every second.
+    /// The interval is recomputed whenever the timeout is changed with [`set_timeout()`](Self::set_timeout).
+    pub fn start_automatic_keep_alive_auto(watchdog_mut_arc: Arc<Mutex<Self>>) -> JoinHandle<KeepAliveExit>{
+        Self::spawn_keep_alive_thread(watchdog_mut_arc, KeepAliveInterval::HalfTimeout, |_| {})
+    }
+
+    /// Starts automatically keeping the system alive, adapting the ping interval to the time left.
+    ///
+    /// This behaves like [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive), 
+    /// but instead of pinging once every second, after each ping the thread reads the time left 
+    /// (see [`get_time_left()`](Self::get_time_left)) and waits for roughly half of it, never less than `min_interval`.
+    /// This way the interval tunes itself to the actual timeout, even if it is changed at runtime.
+    /// If the driver does not support reading the time left, the thread falls back to pinging once every second.
+    /// A zero `min_interval` is rejected: an error is logged and one second is used instead.
+    pub fn start_automatic_keep_alive_adaptive(watchdog_mut_arc: Arc<Mutex<Self>>, min_interval: Duration) -> JoinHandle<KeepAliveExit>{
+        Self::spawn_keep_alive_thread(watchdog_mut_arc, KeepAliveInterval::Adaptive{min: min_interval}, |_| {})
+    }
+
+    /// Keeps the system alive from a separate thread, pinging once every `interval`.
+    ///
+    /// This is a simpler alternative to [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive) for the common case:
+    /// the watchdog is moved into the returned [`WatchdogWorker`], which manages the thread and the sharing internally.
+    /// The worker reports the statistics of the pings with [`stats()`](WatchdogWorker::stats), 
+    /// and [`stop()`](WatchdogWorker::stop) ends the thread and returns the watchdog.
+    /// As for the automatic keep alive thread, the pings stop after 10 consecutive errors
+    /// (see [`set_max_keepalive_errors()`](Self::set_max_keepalive_errors)).
+    ///
+    /// The same disclaimer of [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive) applies.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let worker = Watchdog::keepalive_loop_in_thread(Watchdog::new()?, Duration::from_secs(1));
+    /// // ...
+    /// println!("{:?}", worker.stats());
+    /// let mut wd = worker.stop();
+    /// wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn keepalive_loop_in_thread(wd: Watchdog, interval: Duration) -> WatchdogWorker{
+        let shared = SharedWatchdog::new(wd);
+        let stats = Arc::new(Mutex::new(WorkerStats::default()));
+        let thread_stats = stats.clone();
+        let handle = Self::spawn_keep_alive_thread(shared.watchdog(), KeepAliveInterval::Fixed(interval), move |result| {
+            let mut stats = thread_stats.lock().unwrap_or_else(PoisonError::into_inner);
+            match result{
+                Ok(_) => stats.keep_alives += 1,
+                Err(e) => {
+                    stats.errors += 1;
+                    stats.last_error = Some(*e);
+                },
+            }
+        });
+        WatchdogWorker::new(shared, handle, stats)
+    }
+
+    /// Keeps the system alive from a separate thread, pinging once every `interval`, until the returned guard is dropped.
+    ///
+    /// This is the simplest way to keep the watchdog alive for a scope: the watchdog is moved into the [`KeepAliveGuard`],
+    /// which stops the thread when dropped, and optionally magic closes the watchdog
+    /// (see [`KeepAliveGuard::magic_close_on_drop()`]). See [`KeepAliveGuard`] for the drop order.
+    ///
+    /// The same disclaimer of [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive) applies.
+    pub fn keep_alive_guard(self, interval: Duration) -> KeepAliveGuard{
+        let shared = SharedWatchdog::new(self);
+        let handle = Self::spawn_keep_alive_thread(shared.watchdog(), KeepAliveInterval::Fixed(interval), |_| {});
+        KeepAliveGuard::new(shared, handle)
+    }
+
+    /// Starts automatically keeping the system alive, while also feeding the systemd watchdog.
+    ///
+    /// Daemons run by systemd with the `WatchdogSec=` setting must periodically notify the service manager 
+    /// (`sd_notify("WATCHDOG=1")`), otherwise they are considered hung.
+    /// This behaves like [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive), 
+    /// but after each successful ping of the watchdog, the systemd watchdog is notified as well, 
+    /// so that a single loop feeds both. 
+    /// The notifications are sent at half of the interval configured by systemd in the `WATCHDOG_USEC` 
+    /// environment variable (but at most once per keep alive cycle).
+    /// If the systemd watchdog is not enabled for this process, only the watchdog device is pinged.
+    ///
+    /// Requires the `systemd` feature.
+    #[cfg(feature = "systemd")]
+    pub fn start_automatic_keep_alive_with_systemd(watchdog_mut_arc: Arc<Mutex<Self>>) -> JoinHandle<KeepAliveExit>{
+        let mut notifier = crate::systemd::WatchdogNotifier::from_env();
+        if notifier.is_none(){
+            warn!("The systemd watchdog is not enabled for this process. Only the watchdog device will be pinged.");
+        }
+        Self::spawn_keep_alive_thread(watchdog_mut_arc, KeepAliveInterval::Fixed(DEFAULT_KEEP_ALIVE_INTERVAL), move |result| {
+            if let (Some(notifier), Ok(_)) = (notifier.as_mut(), result){
+                notifier.notify_if_due();
+            }
+        })
+    }
+
+    /// Spawns the 'auto keep alive' thread, calling 'on_keep_alive' with the result of each ping.
+    fn spawn_keep_alive_thread<F>(watchdog_mut_arc: Arc<Mutex<Self>>, interval: KeepAliveInterval, mut on_keep_alive: F) -> JoinHandle<KeepAliveExit>
+    where F: FnMut(&Result<(), Errno>) + Send + 'static {
+        let (tx, rx) = channel::<()>();
+        Self::lock_recovering(&watchdog_mut_arc).msg_sender = Some(tx);
+        let handle = thread::spawn(move || {
+            info!("Automatic keepalive thread started.");
+            // If the driver supports it, verify that each ping is actually registered, 
+            // since a successful ioctl does not guarantee it.
+            let (verify_ping, max_keepalive_errors) = {
+                let wd = Self::lock_recovering(&watchdog_mut_arc);
+                (wd.is_option_supported(&OptionFlags::KeepalivePing).unwrap_or(false), wd.max_keepalive_errors)
+            };
+            let mut keepalive_error_counter = 0;
+            let mut unregistered_ping_counter = 0;
+            let mut interval = interval;
+            if let KeepAliveInterval::Fixed(Duration::ZERO) = interval{
+                error!("The keep alive interval must not be zero. Falling back to {:?}.", DEFAULT_KEEP_ALIVE_INTERVAL);
+                interval = KeepAliveInterval::Fixed(DEFAULT_KEEP_ALIVE_INTERVAL);
+            }
+            if let KeepAliveInterval::Adaptive{min: Duration::ZERO} = interval{
+                // Otherwise the thread would spin once the time left drops below two seconds.
+                error!("The minimum keep alive interval must not be zero. Falling back to {:?}.", DEFAULT_KEEP_ALIVE_INTERVAL);
+                interval = KeepAliveInterval::Adaptive{min: DEFAULT_KEEP_ALIVE_INTERVAL};
+            }
+            // Computed lazily for 'HalfTimeout', and reset whenever the timeout changes.
+            let mut half_timeout = None;
+            let exit = loop{
+                let result;
+                let wait;
+                {
+                    let mut wd = Self::lock_recovering(&watchdog_mut_arc);
+                    // The sender could have been dropped while waiting for the lock (e.g. by a magic close): 
+                    // pinging now could cancel it.
+                    if let Err(TryRecvError::Disconnected) = rx.try_recv(){
+                        warn!("Sender was terminated. Closing 'auto keepalive' thread...");
+                        break KeepAliveExit::Requested;
+                    }
+                    result = if verify_ping{
+                        wd.keep_alive_verified(&mut unregistered_ping_counter, |wd| wd.get_status(&OptionFlags::KeepalivePing))
+                    }
+                    else{
+                        wd.keep_alive_errno()
+                    };
+                    wait = match interval{
+                        KeepAliveInterval::Fixed(duration) => duration,
+                        KeepAliveInterval::HalfTimeout => *half_timeout.get_or_insert_with(|| wd.half_timeout_interval()),
+                        KeepAliveInterval::Adaptive{min} => match wd.get_time_left(){
+                            Ok(time_left) => Duration::from_secs(time_left.max(0) as u64 / 2).max(min),
+                            Err(e) => {
+                                warn!("Couldn't read the time left:{}. Falling back to a fixed interval.", e);
+                                interval = KeepAliveInterval::Fixed(DEFAULT_KEEP_ALIVE_INTERVAL);
+                                DEFAULT_KEEP_ALIVE_INTERVAL
+                            },
+                        },
+                    };
+                }
+                on_keep_alive(&result);
+                if let Err(e) = result{
+                    warn!("Keep alive error {}.", e);
+                    keepalive_error_counter += 1;
+                    if keepalive_error_counter >= max_keepalive_errors{
+                        error!("Max number of consecutive keepalive errors reached ({}). Closing thread...", max_keepalive_errors);
+                        break KeepAliveExit::MaxErrors(keepalive_error_counter);
+                    }
+                }
+                else{
+                    keepalive_error_counter = 0;
+                }
+                // These two 'errors' are used as information, while a message signals that the timeout changed.
+                match rx.recv_timeout(wait){
+                    Ok(_) => {
+                        trace!("Timeout changed. Pinging right away...");
+                        half_timeout = None;
+                        if let KeepAliveInterval::Fixed(duration) = interval{
+                            let timeout = Self::lock_recovering(&watchdog_mut_arc).cached_timeout().filter(|timeout| *timeout > 0);
+                            if let Some(timeout) = timeout.map(|timeout| Duration::from_secs(timeout as u64)){
+                                if duration >= timeout{
+                                    error!("The keep alive interval {:?} is not shorter than the new timeout {:?}. Pinging at half of the timeout instead.", duration, timeout);
+                                    interval = KeepAliveInterval::Fixed(timeout / 2);
+                                }
+                            }
+                        }
+                    },
+                    Err(RecvTimeoutError::Timeout) => trace!("timeout {:?}...", wait),
+                    Err(RecvTimeoutError::Disconnected) => {
+                        // The sender being dropped is an implicit signal that this thread must close.
+                        warn!("Sender was terminated. Closing 'auto keepalive' thread...");
+                        break KeepAliveExit::Requested;
+                    },
+                }
+            };
+            info!("Automatic keepalive thread ended ({:?}).", exit);
+            exit
+        });
+        handle
+    }
+
+    /// Returns half of the timeout, but at least one second, for the 'auto keep alive' thread.
+    ///
+    /// The cached timeout is used if known, since it is updated by [`set_timeout()`](Self::set_timeout).
+    fn half_timeout_interval(&self) -> Duration{
+        let timeout = match self.cached_timeout(){
+            Some(timeout) => Ok(timeout),
+            None => self.get_timeout(),
+        };
+        let duration = match timeout{
+            Ok(timeout) => Duration::from_secs(timeout.max(0) as u64 / 2).max(DEFAULT_KEEP_ALIVE_INTERVAL),
+            Err(e) => {
+                warn!("Couldn't read the timeout:{}. Falling back to {:?}.", e, DEFAULT_KEEP_ALIVE_INTERVAL);
+                DEFAULT_KEEP_ALIVE_INTERVAL
+            },
+        };
+        info!("Keep alive interval:{:?}", duration);
+        duration
+    }
+
+    /// Wakes up the 'auto keep alive' thread, if running, since its interval could be too long for the new timeout.
+    fn notify_timeout_change(&self){
+        if let Some(sender) = &self.msg_sender{
+            let _ = sender.send(());
+        }
+    }
+
+    /// Locks the shared watchdog for the automatic keep alive, recovering the mutex if poisoned.
+    fn lock_recovering(watchdog_mut_arc: &Mutex<Self>) -> MutexGuard<'_, Self>{
+        watchdog_mut_arc.lock().unwrap_or_else(|e| {
+            error!("The watchdog mutex is poisoned, since a thread panicked while holding it. Recovering it to keep pinging.");
+            e.into_inner()
+        })
+    }
+
+    /// Returns the code that re-creates the current configuration of the watchdog.
+    /// 
+    /// The snippet opens the same device and applies the current timeout and pretimeout, e.g.:
+    /// 
+    /// ```text
+    /// let wd = Watchdog::new_by_id(0)?;
+    /// wd.set_timeout(30)?;
+    /// wd.set_pretimeout(10)?;
+    /// ```
+    /// 
+    /// It can be pasted to reproduce a setup, or included in bug reports.
+    /// This is best-effort: the settings that are not supported by the driver (or that can't be read) are omitted.
+    /// If the device can't be opened with [`new()`](Self::new) or [`new_by_id()`](Self::new_by_id), [`open_path()`](Self::open_path) is used.
+    pub fn config_summary(&self) -> String{
+        let mut summary = match self.path.to_str().and_then(|path| path.strip_prefix("/dev/watchdog")){
+            Some("") => String::from("let wd = Watchdog::new()?;\n"),
+            Some(id) if id.parse::<u8>().is_ok() => format!("let wd = Watchdog::new_by_id({})?;\n", id),
+            _ => format!("let wd = Watchdog::open_path({:?})?;\n", self.path),
+        };
+        if self.is_option_supported(&OptionFlags::SetTimeout).unwrap_or(false){
+            if let Ok(timeout) = self.get_timeout(){
+                summary.push_str(&format!("wd.set_timeout({})?;\n", timeout));
+            }
+        }
+        if self.is_option_supported(&OptionFlags::PreTimeout).unwrap_or(false){
+            if let Ok(pretimeout) = self.get_pretimeout(){
+                summary.push_str(&format!("wd.set_pretimeout({})?;\n", pretimeout));
+            }
+        }
+        summary
+    }
+
+    /// Returns a report of everything known about the watchdog, e.g. to be attached to a bug report.
+    /// 
+    /// The multi-line report includes the device path and number, the identity and firmware version,
+    /// the supported options, the current and boot status of the supported status flags (see [`OptionFlags::status_flags()`]),
+    /// the timeout, pretimeout, time left and temperature, and the sysfs 'nowayout', 'state' and pretimeout governor.
+    /// This never fails: each value that can't be read is marked as unsupported, with the reason.
+    pub fn diagnostic_dump(&self) -> String{
+        let mut report = String::new();
+        dump_line(&mut report, "Path", Ok::<_, Errno>(self.path.display()));
+        dump_line(&mut report, "Device number", self.file.metadata()
+            .and_then(|metadata| sysfs::rdev(&metadata))
+            .map(|rdev| {
+                let (major, minor) = sysfs::major_minor(rdev);
+                format!("{}:{}", major, minor)
+            }));
+        dump_line(&mut report, "Identity", self.get_driver_identity());
+        dump_line(&mut report, "Firmware version", self.get_firmware_version());
+        match self.supported_options(){
+            Ok(options) => {
+                dump_line(&mut report, "Supported options", Ok::<_, Errno>(options));
+                // Only the status flags have a status: the other options are capabilities.
+                report.push_str("Status:\n");
+                for option in OptionFlags::status_flags().iter().filter(|option| options.contains(**option)){
+                    report.push_str(&format!("  {}: status {}, boot status {}\n", option,
+                                             dump_value(self.get_status(option)), dump_value(self.get_boot_status(option))));
+                }
+            },
+            Err(e) => dump_line(&mut report, "Supported options", Err::<u32, _>(e)),
+        }
+        dump_line(&mut report, "Timeout", self.get_timeout());
+        dump_line(&mut report, "Pretimeout", self.get_pretimeout());
+        dump_line(&mut report, "Time left", self.get_time_left());
+        dump_line(&mut report, "Temperature", self.get_temp());
+        let sysfs_dir = sysfs::sysfs_dir_for_file(&self.file);
+        for attribute in ["nowayout", "state"]{
+            dump_line(&mut report, attribute, sysfs_dir.as_ref().map_err(|e| e.to_string())
+                .and_then(|dir| sysfs::read_attribute(dir, attribute).map_err(|e| e.to_string())));
+        }
+        dump_line(&mut report, "Pretimeout governor", self.active_pretimeout_governor());
+        report
+    }
+
+    /// Runs the main loop of the program, protected by the watchdog.
+    ///
+    /// This encodes the usual pattern: the watchdog is pinged on every iteration of the loop, 
+    /// and disarmed with [`magic_close()`](Self::magic_close) once the loop exits.
+    /// The closure receives a [`KeepAliveTicker`], whose [`tick()`](KeepAliveTicker::tick) must be called 
+    /// at least once per iteration (i.e. before the timeout expires).
+    ///
+    /// When the closure returns (either with a value or an error), the watchdog is disarmed and the result is returned.
+    /// If the closure panics instead, the watchdog is NOT disarmed, and the system will reset after the timeout.
+    /// Note that the watchdog cannot be disarmed if the driver does not support [`OptionFlags::MagicClose`],
+    /// or if 'nowayout' is set (see [`can_disable()`](Self::can_disable)).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    ///
+    /// # fn do_work() -> bool { false }
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let wd = Watchdog::new()?;
+    /// wd.protect(|ticker| {
+    ///     while do_work(){
+    ///         ticker.tick()?;
+    ///     }
+    ///     Ok::<(), std::io::Error>(())
+    /// })?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn protect<F, T, E>(mut self, main_loop: F) -> Result<T, E>
+    where F: FnOnce(&mut KeepAliveTicker) -> Result<T, E>, E: From<WatchdogError> {
+        let result = main_loop(&mut KeepAliveTicker{watchdog: &mut self});
+        match (self.magic_close(), result){
+            (Ok(_), result) => result,
+            (Err(e), Ok(_)) => Err(e.into()),
+            (Err(e), Err(loop_error)) => {
+                error!("Magic close failed:{}", e);
+                Err(loop_error)
+            },
+        }
+    }
+
+    /// Signals the 'auto keep alive' thread (if any) to exit, without releasing the watchdog.
+    /// 
+    /// The thread exits before its next ping, but it is not waited for: join the handle returned 
+    /// when starting it to be sure it ended. Afterwards, the watchdog must be pinged manually with [`keep_alive()`](Self::keep_alive),
+    /// or a new thread can be started.
+    /// If no thread is running, this does nothing.
+    pub fn stop_automatic_keep_alive(&mut self){
+        // If the automatic keepalive thread is running, send signal to close thread...
+        if self.msg_sender.is_some(){
+            // Drop sender, to let the receiver understand it must exit.
+            self.msg_sender = None;
+        }
+    }
+
+    /// Returns the version of the firmware.
+    /// 
+    /// If available, this returns the firmware version of the card.
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use nix::errno::Errno;
+    /// use log::{info, error};
+    /// 
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// let result = wd.get_firmware_version();
+    /// match result{
     ///    Ok(fw_ver) => info!("Firmware version:{}", fw_ver),
     ///    Err(errno) => {
     ///        error!("error:{}", errno);
@@ -358,49 +1674,65 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_firmware_version(&self) -> Result<u32, Errno> {
-        #[cfg(unix)]
+    pub fn get_firmware_version(&self) -> Result<u32, WatchdogError> {
+        Ok(self.get_support()?.firmware_version)
+    }
+
+    /// Returns the capabilities read upon activation, or issues the `WDIOC_GETSUPPORT` ioctl if they are not available.
+    pub(crate) fn get_support(&self) -> Result<watchdog_info, Errno> {
+        match self.capabilities{
+            Some(wd_info) => Ok(wd_info),
+            None => self.query_support(),
+        }
+    }
+
+    /// Issues the `WDIOC_GETSUPPORT` ioctl and returns the raw structure filled by the driver.
+    ///
+    /// The structure is returned only if the ioctl succeeded: on failure its content is undefined, so it is never read.
+    fn query_support(&self) -> Result<watchdog_info, Errno> {
         let mut wd_info: watchdog_info = watchdog_info::new();
-        let result;
         unsafe{
-            result = ioctl_get_support(self.file.as_raw_fd(),
-                                       &mut wd_info as *mut watchdog_info);
-        }
-        match result{
-            Ok(_) => Ok(wd_info.firmware_version),
-            Err(e) => Err(e),
+            ioctl_get_support(self.file.as_raw_fd(),
+                              &mut wd_info as *mut watchdog_info)?;
         }
+        Ok(wd_info)
     }
 
-    fn bitmask_query(&self, option: &OptionFlags, query: &BitmaskQueryType) -> Result<bool, Errno> {
-        #[cfg(unix)]
+    pub(crate) fn bitmask_read(fd: RawFd, query: &BitmaskQueryType) -> Result<u32, Errno> {
         let mut bitmask: c_int = -1;
         let result;
         match query{
             BitmaskQueryType::GetStatus =>{
                 unsafe{
-                    result = ioctl_get_status(self.file.as_raw_fd(),
+                    result = ioctl_get_status(fd,
                                          &mut bitmask as *mut c_int);
                 }
             }
             BitmaskQueryType::GetBootStatus =>{
                 unsafe{
-                    result = ioctl_get_bootstatus(self.file.as_raw_fd(),
+                    result = ioctl_get_bootstatus(fd,
                                              &mut bitmask as *mut c_int);
                 }
             }
         }
         match result{
-            Ok(_) => {
-                trace!("bitmask: \n{:#034b}\n{:#034b}", 
-                         option.value(),
-                         bitmask);
-                Ok((bitmask as u32 & option.value()) != 0)
-            },
+            Ok(_) => Ok(bitmask as u32),
             Err(e) => Err(e),
         }
     }
 
+    pub(crate) fn bitmask_query(fd: RawFd, option: &OptionFlags, query: &BitmaskQueryType) -> Result<bool, Errno> {
+        let bitmask = Self::bitmask_read(fd, query)?;
+        Ok(Self::bitmask_contains(bitmask, option))
+    }
+
+    fn bitmask_contains(bitmask: u32, option: &OptionFlags) -> bool {
+        trace!("bitmask: \n{:#034b}\n{:#034b}", 
+                 option.value(),
+                 bitmask);
+        (bitmask & option.value()) != 0
+    }
+
     /// Returns the status of an option.
     /// 
     /// For any supported option (see [`is_option_supported()`](Self::is_option_supported)), this returns its related current status.
@@ -424,8 +1756,67 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_status(&self, option: &OptionFlags) -> Result<bool, Errno> {
-        self.bitmask_query(option, &BitmaskQueryType::GetStatus)
+    pub fn get_status(&self, option: &OptionFlags) -> Result<bool, WatchdogError> {
+        Ok(Self::bitmask_query(self.file.as_raw_fd(), option, &BitmaskQueryType::GetStatus)?)
+    }
+
+    /// Returns the current status of every status flag.
+    ///
+    /// Unlike calling [`get_status()`](Self::get_status) for each option, the status is read with a single ioctl,
+    /// and only the flags that are meaningful in the status are reported: [`OptionFlags::Overheat`], [`OptionFlags::FanFault`],
+    /// [`OptionFlags::Extern1`], [`OptionFlags::Extern2`], [`OptionFlags::PowerUnder`], [`OptionFlags::CardReset`],
+    /// [`OptionFlags::PowerOver`] and [`OptionFlags::KeepalivePing`], in this order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use log::info;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if let Ok(statuses) = wd.all_statuses(){
+    ///     for (option, status) in statuses{
+    ///         info!("{}:{}", option, status);
+    ///     }
+    /// }
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn all_statuses(&self) -> Result<Vec<(OptionFlags, bool)>, WatchdogError> {
+        let bitmask = Self::bitmask_read(self.file.as_raw_fd(), &BitmaskQueryType::GetStatus)?;
+        Ok(Self::decompose_status(bitmask))
+    }
+
+    /// Returns the status of every status flag at the last reboot.
+    ///
+    /// See [`all_statuses()`](Self::all_statuses). Like [`get_boot_status()`](Self::get_boot_status), 
+    /// this uses the boot status read upon activation.
+    pub fn all_boot_statuses(&self) -> Result<Vec<(OptionFlags, bool)>, WatchdogError> {
+        let bitmask = match self.boot_status{
+            Some(bitmask) => bitmask,
+            None => Self::bitmask_read(self.file.as_raw_fd(), &BitmaskQueryType::GetBootStatus)?,
+        };
+        Ok(Self::decompose_status(bitmask))
+    }
+
+    /// Decomposes a status bitmask against every status flag.
+    fn decompose_status(bitmask: u32) -> Vec<(OptionFlags, bool)> {
+        OptionFlags::status_flags().iter().map(|option| (*option, Self::bitmask_contains(bitmask, option))).collect()
+    }
+
+    /// Tells if no fault is currently reported by the watchdog.
+    /// 
+    /// The current status (see [`get_status()`](Self::get_status)) is read once, and the watchdog is considered healthy
+    /// if none of these flags is set: [`OptionFlags::Overheat`], [`OptionFlags::FanFault`],
+    /// [`OptionFlags::PowerUnder`] and [`OptionFlags::PowerOver`]. No other flag factors into the verdict.
+    /// 
+    /// If the device does not support reading the status, the error is returned, 
+    /// and the health should be treated as unknown.
+    pub fn is_healthy(&self) -> Result<bool, WatchdogError> {
+        let bitmask = Self::bitmask_read(self.file.as_raw_fd(), &BitmaskQueryType::GetStatus)?;
+        Ok(!FAULT_FLAGS.iter().any(|option| Self::bitmask_contains(bitmask, option)))
     }
 
     /// Returns the status of an option at the last reboot.
@@ -434,6 +1825,11 @@ impl Watchdog {
     /// See also [`get_status()`](Self::get_status) to retrieve the current status.
     /// Note that not all devices support these two calls; some only support one of them.
     /// 
+    /// Some drivers clear the boot status after the first read, so any later read would just return zero.
+    /// To avoid losing the reason of the last reboot, the boot status is read once when the watchdog is activated,
+    /// and this method returns the cached value. Call [`refresh_boot_status()`](Self::refresh_boot_status) to read it again.
+    /// If the first read failed, this method queries the driver every time.
+    /// 
     /// # Examples
     /// 
     /// ```rust
@@ -451,8 +1847,70 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_boot_status(&self, option: &OptionFlags) -> Result<bool, Errno> {
-        self.bitmask_query(option, &BitmaskQueryType::GetBootStatus)
+    pub fn get_boot_status(&self, option: &OptionFlags) -> Result<bool, WatchdogError> {
+        match self.boot_status{
+            Some(bitmask) => Ok(Self::bitmask_contains(bitmask, option)),
+            None => Ok(Self::bitmask_query(self.file.as_raw_fd(), option, &BitmaskQueryType::GetBootStatus)?),
+        }
+    }
+
+    /// Returns the reasons of the last reboot, as reported by the boot status.
+    /// 
+    /// Only the flags describing a reset cause are returned: [`OptionFlags::Overheat`], [`OptionFlags::FanFault`], 
+    /// [`OptionFlags::Extern1`], [`OptionFlags::Extern2`], [`OptionFlags::PowerUnder`], [`OptionFlags::CardReset`] 
+    /// and [`OptionFlags::PowerOver`]. An empty list means that the last reboot was not caused by the watchdog.
+    /// Like [`get_boot_status()`](Self::get_boot_status), this uses the boot status read upon activation.
+    pub fn boot_reason(&self) -> Result<Vec<OptionFlags>, WatchdogError> {
+        let bitmask = match self.boot_status{
+            Some(bitmask) => bitmask,
+            None => Self::bitmask_read(self.file.as_raw_fd(), &BitmaskQueryType::GetBootStatus)?,
+        };
+        Ok(OptionFlags::from_bitmask(bitmask).into_iter().filter(|option| BOOT_REASON_FLAGS.contains(option)).collect())
+    }
+
+    /// Logs the reason of the last reboot.
+    /// 
+    /// Meant to be called once after the activation: if the last reboot was caused by the watchdog, 
+    /// a warning such as "Previous boot was caused by watchdog: Overheat" is logged, otherwise an info line.
+    /// See [`boot_reason()`](Self::boot_reason). If the boot status is not supported, this is logged as well.
+    pub fn log_boot_reason(&self) {
+        match self.boot_reason(){
+            Ok(reasons) if reasons.is_empty() => info!("Previous boot was not caused by watchdog."),
+            Ok(reasons) => {
+                let reasons: Vec<String> = reasons.iter().map(|option| option.to_string()).collect();
+                warn!("Previous boot was caused by watchdog: {}", reasons.join(", "));
+            },
+            Err(e) => info!("Previous boot reason not available:{}", e),
+        }
+    }
+
+    /// Reads the boot status again from the driver, replacing the cached value.
+    ///
+    /// See [`get_boot_status()`](Self::get_boot_status) for more information.
+    pub fn refresh_boot_status(&mut self) -> Result<(), WatchdogError> {
+        let bitmask = Self::bitmask_read(self.file.as_raw_fd(), &BitmaskQueryType::GetBootStatus)?;
+        self.boot_status = Some(bitmask);
+        Ok(())
+    }
+
+    /// Reads the capabilities of the watchdog again.
+    ///
+    /// The capabilities (supported options, firmware version and identity) are static for a device, 
+    /// so they are read once with the `WDIOC_GETSUPPORT` ioctl when the watchdog is activated. 
+    /// [`is_option_supported()`](Self::is_option_supported), [`supported_options()`](Self::supported_options),
+    /// [`get_firmware_version()`](Self::get_firmware_version) and [`get_driver_identity()`](Self::get_driver_identity)
+    /// are then served from this snapshot, without further ioctls.
+    /// This is only needed in the rare case the snapshot must be refreshed.
+    /// If the capabilities could not be read upon activation, the ioctl is issued by each of those methods instead.
+    pub fn refresh_capabilities(&mut self) -> Result<(), WatchdogError> {
+        self.capabilities = Some(self.query_support()?);
+        Ok(())
+    }
+
+    /// Tells if the capabilities were read upon activation (or by [`refresh_capabilities()`](Self::refresh_capabilities)),
+    /// so that the capability queries are served without any ioctl.
+    pub fn capabilities_cached(&self) -> bool {
+        self.capabilities.is_some()
     }
 
     /// Tells if an option is supported.
@@ -478,28 +1936,59 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn is_option_supported(&self, option: &OptionFlags) -> Result<bool, Errno> {
-        #[cfg(unix)]
-        let mut wd_info: watchdog_info = watchdog_info::new();
-        let result;
-        unsafe{
-            result = ioctl_get_support(self.file.as_raw_fd(),
-                                       &mut wd_info as *mut watchdog_info);
-        }
-        match result{
-            Ok(_) => {
+    pub fn is_option_supported(&self, option: &OptionFlags) -> Result<bool, WatchdogError> {
+        match self.get_support(){
+            Ok(wd_info) => {
                 trace!("options bitmask: \n{:#034b}\n{:#034b}", 
                          option.value(),
                          wd_info.options);
                 Ok((wd_info.options & option.value()) != 0)
             },
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
+    /// Returns the capabilities of the watchdog: supported options, firmware version and identity.
+    ///
+    /// This is a snapshot of what [`supported_options()`](Self::supported_options), 
+    /// [`get_firmware_version()`](Self::get_firmware_version) and [`get_driver_identity()`](Self::get_driver_identity) 
+    /// return, obtained with a single `WDIOC_GETSUPPORT` ioctl (or none, if the capabilities are cached).
+    /// The identity is always the one reported by the ioctl, with the trailing NUL bytes removed.
+    pub fn get_info(&self) -> Result<WatchdogInfo, WatchdogError> {
+        Ok(WatchdogInfo::from_raw(&self.get_support()?))
+    }
+
+    /// Returns all the options supported by the watchdog.
+    /// 
+    /// A single query returns the whole set: checking many options this way is cheaper than calling 
+    /// [`is_option_supported()`](Self::is_option_supported) for each of them.
+    ///
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::{Watchdog, OptionFlags};
+    /// 
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// let options = wd.supported_options()?;
+    /// println!("Supported options: {}", options);
+    /// if options.contains(OptionFlags::MagicClose){
+    ///     wd.magic_close()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn supported_options(&self) -> Result<OptionSet, WatchdogError> {
+        Ok(OptionSet::from_bits(self.get_support()?.options))
+    }
+
     /// Returns the watchdog driver identifier.
     /// 
     /// This returns a String containing the identifier for the watchdog driver.
+    /// 
+    /// By default the identity is obtained with the `WDIOC_GETSUPPORT` ioctl. If enabled with
+    /// [`WatchdogBuilder::identity_from_sysfs()`](crate::builder::WatchdogBuilder::identity_from_sysfs),
+    /// it is read from sysfs first ('/sys/class/watchdog/watchdogN/identity'), falling back to the ioctl if not available.
     ///
     /// # Examples
     /// 
@@ -515,37 +2004,42 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_driver_identity(&self) -> Result<String, Errno> {
-        #[cfg(unix)]
-        let mut wd_info: watchdog_info = watchdog_info::new();
-        let result;
-        unsafe{
-            result = ioctl_get_support(self.file.as_raw_fd(),
-                                       &mut wd_info as *mut watchdog_info);
-        }
-        // allocate new string containing the converted u8 array.
-        let string_ident = String::from_utf8_lossy(&wd_info.identity).into_owned();
-        match result{
-            Ok(_) => Ok(string_ident),
-            Err(e) => Err(e),
+    pub fn get_driver_identity(&self) -> Result<String, WatchdogError> {
+        if self.identity_from_sysfs{
+            match sysfs::sysfs_dir_for_file(&self.file).and_then(|dir| sysfs::read_attribute(&dir, "identity")){
+                Ok(identity) => return Ok(identity),
+                Err(e) => trace!("Identity not available from sysfs:{}. Falling back to the ioctl.", e),
+            }
         }
+        // Only the identity is converted, once the capabilities are known to be valid.
+        let wd_info = self.get_support()?;
+        Ok(identity_to_string(&wd_info.identity))
+    }
+
+    /// Tells if this is the software watchdog.
+    ///
+    /// The software watchdog is provided by the `softdog` kernel module, and is recognized by its driver identity
+    /// (see [`get_driver_identity()`](Self::get_driver_identity)).
+    /// It is useful for testing, since it does not involve any real hardware.
+    pub fn is_software_watchdog(&self) -> Result<bool, WatchdogError> {
+        let identity = self.get_driver_identity()?;
+        Ok(identity == SOFTDOG_IDENTITY)
     }
 
-    fn int_getter(&self, getter_type: IntGetterType) -> Result<i32, Errno> {
-        #[cfg(unix)]
+    pub(crate) fn int_getter(fd: RawFd, getter_type: IntGetterType) -> Result<i32, Errno> {
         let mut value: c_int = -1;
         let result = match getter_type{
-            IntGetterType::GetTimeout => unsafe{
-                ioctl_get_timeout(self.file.as_raw_fd(), &mut value as *mut c_int)
+            IntGetterType::Timeout => unsafe{
+                ioctl_get_timeout(fd, &mut value as *mut c_int)
             },
-            IntGetterType::GetPreTimeout => unsafe{
-                ioctl_get_pretimeout(self.file.as_raw_fd(), &mut value as *mut c_int)
+            IntGetterType::PreTimeout => unsafe{
+                ioctl_get_pretimeout(fd, &mut value as *mut c_int)
             },
-            IntGetterType::GetTimeLeft => unsafe{
-                ioctl_get_time_left(self.file.as_raw_fd(), &mut value as *mut c_int)
+            IntGetterType::TimeLeft => unsafe{
+                ioctl_get_time_left(fd, &mut value as *mut c_int)
             },
-            IntGetterType::GetTemp => unsafe{
-                ioctl_get_temp(self.file.as_raw_fd(), &mut value as *mut c_int)
+            IntGetterType::Temp => unsafe{
+                ioctl_get_temp(fd, &mut value as *mut c_int)
             },
         };
         match result{
@@ -572,8 +2066,31 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_timeout(&self) -> Result<i32, Errno> {
-        self.int_getter(IntGetterType::GetTimeout)
+    pub fn get_timeout(&self) -> Result<i32, WatchdogError> {
+        Ok(Self::int_getter(self.file.as_raw_fd(), IntGetterType::Timeout)?)
+    }
+
+    /// Returns the configured timeout as a [`Duration`].
+    ///
+    /// Preferred over [`get_timeout()`](Self::get_timeout), since the unit is explicit.
+    /// A negative value returned by the driver is reported as an ERANGE error.
+    pub fn get_timeout_duration(&self) -> Result<Duration, WatchdogError> {
+        secs_to_duration(self.get_timeout()?)
+    }
+
+    /// Returns the configured timeout, without querying the driver.
+    ///
+    /// The timeout is read once when the watchdog is activated, and updated by every successful 
+    /// [`set_timeout()`](Self::set_timeout). This is meant for hot paths that need the value frequently.
+    /// The value may be stale if the timeout was changed externally (e.g. by another process, or through a 
+    /// [`duplicate()`](Self::duplicate) of this instance): use [`get_timeout()`](Self::get_timeout) for a fresh read.
+    ///
+    /// Returns None if the timeout could not be read upon activation.
+    pub fn cached_timeout(&self) -> Option<i32> {
+        match self.cached_timeout.load(Ordering::Relaxed){
+            TIMEOUT_UNKNOWN => None,
+            timeout => Some(timeout),
+        }
     }
 
     /// Returns the configured pre-timeout, if suppported.
@@ -607,8 +2124,16 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_pretimeout(&self) -> Result<i32, Errno> {
-        self.int_getter(IntGetterType::GetPreTimeout)
+    pub fn get_pretimeout(&self) -> Result<i32, WatchdogError> {
+        Ok(Self::int_getter(self.file.as_raw_fd(), IntGetterType::PreTimeout)?)
+    }
+
+    /// Returns the configured pre-timeout as a [`Duration`], if supported.
+    ///
+    /// Preferred over [`get_pretimeout()`](Self::get_pretimeout), since the unit is explicit.
+    /// A negative value returned by the driver is reported as an ERANGE error.
+    pub fn get_pretimeout_duration(&self) -> Result<Duration, WatchdogError> {
+        secs_to_duration(self.get_pretimeout()?)
     }
 
     /// Returns the time left before reset.
@@ -630,14 +2155,114 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_time_left(&self) -> Result<i32, Errno> {
-        self.int_getter(IntGetterType::GetTimeLeft)
+    pub fn get_time_left(&self) -> Result<i32, WatchdogError> {
+        Ok(Self::int_getter(self.file.as_raw_fd(), IntGetterType::TimeLeft)?)
+    }
+
+    /// Returns the time left before the system resets as a [`Duration`], if supported.
+    ///
+    /// Preferred over [`get_time_left()`](Self::get_time_left), since the unit is explicit.
+    /// A negative value returned by the driver is reported as an ERANGE error.
+    pub fn get_time_left_duration(&self) -> Result<Duration, WatchdogError> {
+        secs_to_duration(self.get_time_left()?)
+    }
+
+    /// Tells if the system is within the given margin of a reset, i.e. the time left is less than or equal to `margin`.
+    ///
+    /// This requires the support of [`get_time_left()`](Self::get_time_left) (`WDIOC_GETTIMELEFT`): 
+    /// on drivers lacking it the error is returned as is (usually ENOTTY or EOPNOTSUPP). 
+    /// See [`time_left_estimate()`](Self::time_left_estimate) for a software alternative.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::Watchdog;
+    /// use std::time::Duration;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if wd.is_close_to_reset(Duration::from_secs(5)).unwrap_or(true){
+    ///     wd.keep_alive()?;
+    /// }
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn is_close_to_reset(&self, margin: Duration) -> Result<bool, WatchdogError> {
+        Ok(self.get_time_left_duration()? <= margin)
+    }
+
+    /// Blocks until the time left drops to or below the given threshold.
+    ///
+    /// The time left is polled with [`is_close_to_reset()`](Self::is_close_to_reset) every second, 
+    /// or every quarter of the threshold if shorter (but at least every 10 ms). 
+    /// This is meant for tests observing the behavior right before a reset: the watchdog is NOT pinged meanwhile.
+    ///
+    /// If the driver does not support [`get_time_left()`](Self::get_time_left), the error is returned right away,
+    /// instead of waiting forever.
+    pub fn wait_until_time_left(&self, threshold: Duration) -> Result<(), WatchdogError> {
+        let poll_interval = (threshold / 4).clamp(Duration::from_millis(10), Duration::from_secs(1));
+        while !self.is_close_to_reset(threshold)?{
+            thread::sleep(poll_interval);
+        }
+        Ok(())
+    }
+
+    /// Estimates the time left before the system resets, without querying the driver.
+    ///
+    /// Many drivers do not support [`get_time_left()`](Self::get_time_left). This computes a software estimate instead:
+    /// the [cached timeout](Self::cached_timeout) minus the time elapsed since the last successful 
+    /// [`keep_alive()`](Self::keep_alive) of this instance (or since the activation), clamped at zero.
+    ///
+    /// This is only an approximation: it ignores the driver-side jitter and granularity, 
+    /// and the pings sent by other means (e.g. a [`duplicate()`](Self::duplicate), or a driver pinging on 
+    /// [`set_timeout()`](Self::set_timeout)), which make the real time left longer than estimated.
+    ///
+    /// Returns None if the timeout is unknown.
+    pub fn time_left_estimate(&self) -> Option<Duration> {
+        let timeout = Duration::from_secs(self.cached_timeout()?.max(0) as u64);
+        Some(timeout.saturating_sub(self.last_keep_alive.unwrap_or(self.activated_at).elapsed()))
+    }
+
+    /// Measures the effective timeout of the software watchdog.
+    ///
+    /// **Warning**: this method stops pinging the watchdog for up to `max_wait`, 
+    /// so that the real timeout window can be observed. On real hardware this would be destructive, 
+    /// therefore it refuses to run on anything other than the software watchdog 
+    /// (see [`is_software_watchdog()`](Self::is_software_watchdog)), returning a [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`].
+    /// Even on the software watchdog, the system WILL reboot if the timeout elapses during the measurement,
+    /// e.g. if `max_wait` is longer than the timeout and the time left cannot be read anymore.
+    ///
+    /// This is a diagnostic tool meant to confirm that the real window matches the configured timeout.
+    /// The watchdog is pinged once, then [`get_time_left()`](Self::get_time_left) is polled until 
+    /// the time left drops to 1 second or `max_wait` elapses; the effective timeout is the elapsed time 
+    /// plus the time left at that moment, with the resolution of one second. 
+    /// Finally the watchdog is pinged again.
+    pub fn measure_effective_timeout(&mut self, max_wait: Duration) -> Result<Duration, WatchdogError> {
+        if !self.is_software_watchdog()?{
+            return Err(io::Error::new(io::ErrorKind::Unsupported, 
+                "the effective timeout can only be measured on the software watchdog").into());
+        }
+        warn!("Measuring the effective timeout: the watchdog will not be pinged for up to {:?}.", max_wait);
+        self.keep_alive()?;
+        let start = Instant::now();
+        let mut time_left = self.get_time_left()?;
+        while time_left > 1 && start.elapsed() < max_wait{
+            thread::sleep(Duration::from_millis(100));
+            time_left = self.get_time_left()?;
+        }
+        let elapsed = start.elapsed();
+        self.keep_alive()?;
+        let effective_timeout = elapsed + Duration::from_secs(time_left.max(0) as u64);
+        info!("Effective timeout:{:?}", effective_timeout);
+        Ok(effective_timeout)
     }
 
     /// Returns the current temperature.
     /// 
     /// Some drivers can measure the temperature. 
-    /// The returned value is the temperature in degrees fahrenheit.
+    /// The returned value is the temperature in degrees Fahrenheit, as reported by the kernel.
+    /// See [`get_temp_celsius()`](Self::get_temp_celsius) for degrees Celsius.
     /// 
     /// # Examples
     /// 
@@ -653,8 +2278,16 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn get_temp(&self) -> Result<i32, Errno> {
-        self.int_getter(IntGetterType::GetTemp)
+    pub fn get_temp(&self) -> Result<i32, WatchdogError> {
+        Ok(Self::int_getter(self.file.as_raw_fd(), IntGetterType::Temp)?)
+    }
+
+    /// Returns the current temperature in degrees Celsius.
+    /// 
+    /// Converts the value returned by [`get_temp()`](Self::get_temp), which is in degrees Fahrenheit.
+    pub fn get_temp_celsius(&self) -> Result<f32, WatchdogError> {
+        let fahrenheit = self.get_temp()? as f32;
+        Ok((fahrenheit - 32.0) * 5.0 / 9.0)
     }
 
     /// Configures the timeout, if supported.
@@ -664,36 +2297,181 @@ impl Watchdog {
     /// The argument is an integer representing the timeout in seconds. If the value is unsupported, the function will return an EINVAL error.
     /// The driver returns the real timeout used in the same variable, and this timeout might differ from the requested one due to limitation of the hardware
     /// 
+    /// A timeout that is not strictly positive is rejected with EINVAL before reaching the driver.
+    /// 
+    /// If the 'auto keep alive' thread is running (see [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive)),
+    /// it is notified of the change: it pings the watchdog right away and recomputes its interval on the new timeout 
+    /// (see [`start_automatic_keep_alive_auto()`](Self::start_automatic_keep_alive_auto) 
+    /// and [`start_automatic_keep_alive_adaptive()`](Self::start_automatic_keep_alive_adaptive)), 
+    /// instead of completing a wait that could now be longer than the timeout.
+    /// A fixed interval is kept, unless it is not shorter than the new timeout
+    /// (see [`start_automatic_keep_alive_with_interval()`](Self::start_automatic_keep_alive_with_interval)).
+    /// 
+    /// # Examples
+    /// 
+    /// ```rust
+    /// use watchdog_device::{Watchdog, OptionFlags};
+    /// use nix::errno::Errno;
+    /// use log::info;
+    /// 
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if wd.is_option_supported(&OptionFlags::SetTimeout).unwrap(){
+    ///     let requested_timeout = 14;
+    ///     let returned_configured_timeout = wd.set_timeout(requested_timeout).unwrap();
+    ///     info!("requested timeout:{} - returned value:{}", requested_timeout, returned_configured_timeout);
+    /// }
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_timeout(&self, timeout: i32) -> Result<i32, WatchdogError> {
+        if timeout <= 0{
+            error!("Invalid timeout:{} secs. The timeout must be positive.", timeout);
+            return Err(Errno::EINVAL.into());
+        }
+        let mut timeout_for_ioctl: c_int = timeout;
+        let result;
+        unsafe{
+            result = ioctl_set_timeout(self.file.as_raw_fd(), 
+                                       &mut timeout_for_ioctl as *mut c_int);
+        }
+        match result{
+            Ok(_) => {
+                self.cached_timeout.store(timeout_for_ioctl, Ordering::Relaxed);
+                self.notify_timeout_change();
+                Ok(timeout_for_ioctl)
+            },
+            Err(e) => Err(self.set_timeout_error(timeout, e)),
+        }
+    }
+
+    /// Sets the timeout from a [`Duration`], returning the timeout actually applied by the driver.
+    ///
+    /// Watchdog drivers work with whole seconds, so any fractional part is rounded up
+    /// (e.g. 1.2s becomes 2s): the applied timeout is never shorter than requested.
+    /// A duration exceeding `i32::MAX` seconds is rejected with EINVAL, without touching the device.
+    pub fn set_timeout_duration(&self, timeout: Duration) -> Result<Duration, WatchdogError> {
+        secs_to_duration(self.set_timeout(duration_to_secs(timeout)?)?)
+    }
+
+    /// Configures exactly the requested timeout, or fails.
+    ///
+    /// Unlike [`set_timeout()`](Self::set_timeout), which reports the timeout actually applied by the driver, 
+    /// this fails with [`WatchdogError::Clamped`] if the driver did not honor the exact value. 
+    /// In that case, the previous timeout is restored (if known), so that the watchdog is left as it was.
+    ///
+    /// Prefer this when the safety case depends on the exact window, e.g. if a longer timeout would allow 
+    /// a hung system to run for too long, or a shorter one would reset it during a legitimate long operation.
+    /// Use [`set_timeout()`](Self::set_timeout) when any value close to the requested one is acceptable,
+    /// and adapt the keep alive cadence to the returned one.
+    ///
+    /// If the driver rejects the change because the watchdog has 'nowayout' set (see [`can_disable()`](Self::can_disable)),
+    /// [`WatchdogError::TimeoutLocked`] is returned: on such devices, the timeout must be configured before arming
+    /// (e.g. with the driver module parameters).
+    pub fn set_timeout_strict(&self, timeout: i32) -> Result<(), WatchdogError> {
+        let previous_timeout = self.cached_timeout();
+        let applied = self.set_timeout(timeout)?;
+        if applied == timeout{
+            return Ok(());
+        }
+        if let Some(previous) = previous_timeout{
+            if let Err(e) = self.set_timeout(previous){
+                error!("Couldn't restore the previous timeout {}:{}", previous, e);
+            }
+        }
+        Err(WatchdogError::Clamped{requested: timeout, applied})
+    }
+
+    /// Configures the timeout, telling whether the driver applied the requested value as is.
+    ///
+    /// This behaves as [`set_timeout()`](Self::set_timeout), but compares the timeout returned by the driver
+    /// with the requested one. Unlike [`set_timeout_strict()`](Self::set_timeout_strict), an adjusted timeout is kept:
+    /// the caller decides whether it is acceptable.
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use watchdog_device::{Watchdog, SetTimeoutOutcome};
+    /// use log::warn;
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let wd = Watchdog::new()?;
+    /// if let SetTimeoutOutcome::Adjusted{requested, actual} = wd.set_timeout_checked(30)?{
+    ///     warn!("requested {}s, the hardware gave {}s", requested, actual);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_timeout_checked(&self, requested: i32) -> Result<SetTimeoutOutcome, WatchdogError> {
+        let actual = self.set_timeout(requested)?;
+        if actual == requested{
+            return Ok(SetTimeoutOutcome::Exact(actual));
+        }
+        info!("Requested timeout {} secs, the driver applied {} secs", requested, actual);
+        Ok(SetTimeoutOutcome::Adjusted{requested, actual})
+    }
+
+    /// Explains why the driver rejected a timeout change.
+    ///
+    /// Some drivers reject any change once armed, if 'nowayout' is set: this is reported as [`WatchdogError::TimeoutLocked`],
+    /// instead of a confusing EINVAL/EPERM.
+    fn set_timeout_error(&self, requested: i32, e: Errno) -> WatchdogError {
+        if matches!(e, Errno::EINVAL | Errno::EPERM) && matches!(self.can_disable(), Ok(false)){
+            warn!("Timeout change to {} rejected with {}, with nowayout set.", requested, e);
+            return WatchdogError::TimeoutLocked{requested, errno: e};
+        }
+        e.into()
+    }
+
+    /// Configures the smallest timeout accepted by the driver, if supported.
+    ///
+    /// This is useful to quickly approach a controlled reset, or to test the timeout behaviour.
+    /// The minimum is read from the `min_timeout` sysfs attribute of the device.
+    /// If that is not available, the minimum is probed by requesting increasing timeouts starting from 1 second,
+    /// up to the currently configured one: the first value accepted by the driver is kept 
+    /// (drivers that round the requested value report the real timeout, which is then used as the minimum).
+    /// Note that each probing attempt issues a separate ioctl.
+    ///
+    /// Returns the timeout configured by the driver, in seconds.
+    ///
     /// # Examples
-    /// 
+    ///
     /// ```rust
     /// use watchdog_device::{Watchdog, OptionFlags};
     /// use nix::errno::Errno;
     /// use log::info;
-    /// 
+    ///
     /// # fn main() -> Result<(), std::io::Error> {
     /// let mut wd = Watchdog::new()?;
     /// if wd.is_option_supported(&OptionFlags::SetTimeout).unwrap(){
-    ///     let requested_timeout = 14;
-    ///     let returned_configured_timeout = wd.set_timeout(requested_timeout).unwrap();
-    ///     info!("requested timeout:{} - returned value:{}", requested_timeout, returned_configured_timeout);
+    ///     info!("Minimum timeout:{}", wd.set_timeout_min().unwrap());
     /// }
     /// # wd.magic_close()?;
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_timeout(&self, timeout: i32) -> Result<i32, Errno> {
-        #[cfg(unix)]
-        let mut timeout_for_ioctl: c_int = timeout;
-        let result;
-        unsafe{
-            result = ioctl_set_timeout(self.file.as_raw_fd(), 
-                                       &mut timeout_for_ioctl as *mut c_int);
+    pub fn set_timeout_min(&self) -> Result<i32, WatchdogError> {
+        let sysfs_min = sysfs::sysfs_dir_for_file(&self.file)
+            .and_then(|dir| sysfs::read_attribute(&dir, "min_timeout"))
+            .ok()
+            .and_then(|value| value.parse::<i32>().ok());
+        if let Some(min_timeout) = sysfs_min {
+            // A minimum of 0 means that the driver doesn't declare any.
+            return self.set_timeout(min_timeout.max(1));
         }
-        match result{
-            Ok(_) => Ok(timeout_for_ioctl),
-            Err(e) => Err(e),
+        info!("min_timeout not available from sysfs. Probing the minimum timeout...");
+        let current_timeout = self.get_timeout()?;
+        let mut result = Err(Errno::EINVAL.into());
+        for timeout in 1..=current_timeout.max(1){
+            result = self.set_timeout(timeout);
+            match result{
+                Ok(_) => break,
+                Err(WatchdogError::InvalidArgument) => trace!("Timeout {} rejected.", timeout),
+                Err(_) => break,
+            }
         }
+        result
     }
 
     /// Configures the pre-timeout, if suppported.
@@ -712,6 +2490,10 @@ impl Watchdog {
     /// The argument is an integer representing the timeout in seconds. 
     /// The driver returns the real timeout used in the same variable, 
     /// and this timeout might differ from the requested one due to limitation of the hardware.
+    /// A negative pretimeout is rejected with EINVAL before reaching the driver.
+    /// Since the pretimeout fires that many seconds before the reset, a pretimeout not shorter than the current timeout
+    /// (see [`get_timeout()`](Self::get_timeout)) is meaningless: it is rejected with [`WatchdogError::PretimeoutTooLong`], 
+    /// before reaching the driver as well. If the timeout cannot be read, the driver decides.
     /// 
     /// Not all watchdog drivers will support a pretimeout.
     /// 
@@ -733,8 +2515,21 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn set_pretimeout(&self, pretimeout: i32) -> Result<i32, Errno> {
-        #[cfg(unix)]
+    pub fn set_pretimeout(&self, pretimeout: i32) -> Result<i32, WatchdogError> {
+        if pretimeout < 0{
+            error!("Invalid pretimeout:{} secs. The pretimeout must not be negative (0 disables it).", pretimeout);
+            return Err(Errno::EINVAL.into());
+        }
+        if pretimeout > 0{
+            match self.get_timeout(){
+                Ok(timeout) if pretimeout >= timeout => {
+                    error!("Invalid pretimeout:{} secs, not shorter than the timeout:{} secs.", pretimeout, timeout);
+                    return Err(WatchdogError::PretimeoutTooLong{pretimeout, timeout});
+                },
+                Ok(_) => {},
+                Err(e) => trace!("Couldn't read the timeout to validate the pretimeout:{}", e),
+            }
+        }
         let mut pretimeout_for_ioctl: c_int = pretimeout;
         let result;
         unsafe{
@@ -743,27 +2538,261 @@ impl Watchdog {
         }
         match result{
             Ok(_) => Ok(pretimeout_for_ioctl),
-            Err(e) => Err(e),
+            Err(e) => Err(e.into()),
         }
     }
 
+    /// Sets the pre-timeout from a [`Duration`], returning the pre-timeout actually applied by the driver.
+    ///
+    /// As with [`set_timeout_duration()`](Self::set_timeout_duration), any fractional part is rounded up,
+    /// and a duration exceeding `i32::MAX` seconds is rejected with EINVAL, without touching the device.
+    pub fn set_pretimeout_duration(&self, pretimeout: Duration) -> Result<Duration, WatchdogError> {
+        secs_to_duration(self.set_pretimeout(duration_to_secs(pretimeout)?)?)
+    }
+
     /// Sets a watchdog operation.
     /// 
     /// This can be used to control some aspects of the card operation, if supported.
     /// The [`SetOptionFlags`] enum lists all the operations that is possible to trigger.
-    pub fn set_option(&self, option: &SetOptionFlags) -> Result<(), Errno> {
-        #[cfg(unix)]
-        let mut option_to_set: c_int = 
-            option.value().try_into().expect("option not convertible to c_int");
+    /// See [`set_options()`](Self::set_options) to set many of them at once.
+    pub fn set_option(&self, option: &SetOptionFlags) -> Result<(), WatchdogError> {
+        self.set_options(std::slice::from_ref(option))
+    }
+
+    /// Sets many watchdog operations at once.
+    ///
+    /// The operations are OR-ed together into the bitmask of a single `WDIOC_SETOPTIONS` ioctl,
+    /// so that drivers apply them atomically (e.g. [`SetOptionFlags::EnableCard`] together with [`SetOptionFlags::TempPanic`]).
+    ///
+    /// # Examples
+    ///
+    /// ```rust,no_run
+    /// use watchdog_device::{Watchdog, SetOptionFlags};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if let Err(e) = wd.set_options(&[SetOptionFlags::EnableCard, SetOptionFlags::TempPanic]){
+    ///     println!("Error {}", e);
+    /// }
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn set_options(&self, options: &[SetOptionFlags]) -> Result<(), WatchdogError> {
+        let bitmask = options.iter().fold(0, |bitmask, option| bitmask | option.value());
+        let mut option_to_set: c_int = bitmask.try_into().map_err(|_| {
+            error!("Options {:?} not convertible to c_int.", options);
+            Errno::EINVAL
+        })?;
         let result;
         unsafe{
             result = ioctl_set_options(self.file.as_raw_fd(), 
                                        &mut option_to_set as *mut c_int);
         }
         match result{
-            Ok(res) => {trace!("Set_option {} returned {}.", option, res); Ok(())},
-            Err(e) => Err(e),
+            Ok(res) => {trace!("Set_options {:?} returned {}.", options, res); Ok(())},
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Stops the watchdog, without closing it.
+    ///
+    /// This is [`set_option()`](Self::set_option) with [`SetOptionFlags::DisableCard`], which some drivers support 
+    /// even without [`OptionFlags::MagicClose`]: a clean shutdown can thus try [`magic_close()`](Self::magic_close) first,
+    /// and fall back to this. The watchdog can be restarted with [`enable()`](Self::enable).
+    ///
+    /// If the watchdog has 'nowayout' set (see [`is_nowayout()`](Self::is_nowayout)), it cannot be stopped:
+    /// EBUSY is returned without issuing the ioctl, as the driver would do.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::{Watchdog, OptionFlags};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if wd.is_option_supported(&OptionFlags::MagicClose)?{
+    ///     wd.magic_close()?;
+    /// }
+    /// else if let Err(e) = wd.disable(){
+    ///     println!("The watchdog is still running: {}", e);
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn disable(&self) -> Result<(), WatchdogError> {
+        if matches!(self.is_nowayout(), Ok(true)){
+            warn!("The watchdog can't be disabled, with nowayout set.");
+            return Err(Errno::EBUSY.into());
+        }
+        self.set_option(&SetOptionFlags::DisableCard)?;
+        warn!("Watchdog disabled. It will NOT restart the system.");
+        Ok(())
+    }
+
+    /// Starts the watchdog again, after [`disable()`](Self::disable).
+    ///
+    /// This is [`set_option()`](Self::set_option) with [`SetOptionFlags::EnableCard`].
+    pub fn enable(&self) -> Result<(), WatchdogError> {
+        self.set_option(&SetOptionFlags::EnableCard)?;
+        info!("Watchdog enabled.");
+        Ok(())
+    }
+
+    /// Makes the kernel panic when the card reports an overheat.
+    ///
+    /// This is [`set_option()`](Self::set_option) with [`SetOptionFlags::TempPanic`]. The two are related as follows:
+    /// [`OptionFlags::Overheat`] tells that the card monitors the temperature, and reports the overheat 
+    /// through [`get_status()`](Self::get_status) and [`get_boot_status()`](Self::get_boot_status);
+    /// [`SetOptionFlags::TempPanic`] asks the driver to panic the kernel on the overheat, instead of just reporting it.
+    /// Hence, the Overheat capability is checked first: if it is not supported, a [`WatchdogError::Io`] 
+    /// with [`io::ErrorKind::Unsupported`] is returned, without issuing the ioctl.
+    pub fn enable_temp_panic(&self) -> Result<(), WatchdogError> {
+        if !self.is_option_supported(&OptionFlags::Overheat)?{
+            return Err(io::Error::new(io::ErrorKind::Unsupported, 
+                "the watchdog does not monitor the temperature (Overheat not supported)").into());
+        }
+        self.set_option(&SetOptionFlags::TempPanic)?;
+        info!("Kernel panic on overheat enabled.");
+        Ok(())
+    }
+
+    /// Tells if the watchdog can be disabled.
+    ///
+    /// If the kernel was built with CONFIG_WATCHDOG_NOWAYOUT, or the 'nowayout' module parameter is set,
+    /// the watchdog cannot be stopped once started: neither with [`SetOptionFlags::DisableCard`] 
+    /// (see [`set_option()`](Self::set_option)) nor with [`magic_close()`](Self::magic_close).
+    /// This reads the 'nowayout' setting from sysfs, so that maintenance workflows can decide up front
+    /// whether the watchdog can be paused, instead of failing midway.
+    ///
+    /// If this returns false, the only way to stop the watchdog is a reboot.
+    /// A [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`] is returned if the setting is not exposed by sysfs.
+    ///
+    /// The 'nowayout' setting is all it takes: sysfs only exposes it for the drivers registered with the kernel watchdog core,
+    /// which handles the `WDIOC_SETOPTIONS` ioctl itself, for every driver. The core stops the watchdog on
+    /// [`SetOptionFlags::DisableCard`] unless 'nowayout' is set, and if the hardware can't be stopped,
+    /// it keeps pinging it on behalf of the application, so that no reset happens.
+    /// The support of the ioctl can't be probed anyway, since issuing it has side effects.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::{Watchdog, SetOptionFlags};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// if wd.can_disable()?{
+    ///     wd.set_option(&SetOptionFlags::DisableCard)?;
+    /// }
+    /// # wd.magic_close()?;
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn can_disable(&self) -> Result<bool, WatchdogError> {
+        // A 'nowayout' in sysfs implies the watchdog core, which always supports 'DisableCard'.
+        Ok(!self.is_nowayout()?)
+    }
+
+    /// Tells if the watchdog has 'nowayout' set, i.e. [`magic_close()`](Self::magic_close) will NOT stop it.
+    ///
+    /// The setting comes either from the kernel configuration (CONFIG_WATCHDOG_NOWAYOUT) or from the 'nowayout' 
+    /// module parameter of the driver, and is read from the sysfs directory of the opened device.
+    /// If sysfs does not expose it, the setting is unknown: a [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`] 
+    /// is returned, rather than guessing.
+    /// See [`can_disable()`](Self::can_disable) for the opposite question.
+    pub fn is_nowayout(&self) -> Result<bool, WatchdogError> {
+        let dir = sysfs::sysfs_dir_for_file(&self.file)?;
+        let nowayout = sysfs::read_attribute(&dir, "nowayout")?;
+        Ok(nowayout != "0")
+    }
+
+    /// Tells if the watchdog is currently running, i.e. it must be pinged to prevent a reset.
+    ///
+    /// The state is read from the 'state' sysfs attribute ("active" or "inactive"), so that a supervisor can tell
+    /// whether it needs to ping at all (e.g. after [`disable()`](Self::disable)).
+    /// The sysfs directory is not derived from the ID in the device name, but from the device number of the opened file:
+    /// '/sys/dev/char/MAJOR:MINOR', or '/sys/class/watchdog/watchdog0' for the legacy '/dev/watchdog' node. 
+    /// This way, renamed nodes and symlinks are handled correctly.
+    ///
+    /// If sysfs does not expose the state, the watchdog is assumed to be running, since opening the device starts it.
+    pub fn is_running(&self) -> Result<bool, WatchdogError> {
+        let dir = sysfs::sysfs_dir_for_file(&self.file)?;
+        match sysfs::read_attribute(&dir, "state"){
+            Ok(state) => Ok(state != "inactive"),
+            Err(e) if e.kind() == io::ErrorKind::Unsupported => {
+                trace!("State not available:{}. Assuming the watchdog is running.", e);
+                Ok(true)
+            },
+            Err(e) => Err(e.into()),
+        }
+    }
+
+    /// Tells if the system would reset, were the process to exit now.
+    ///
+    /// This answers the question "is it safe to stop?", combining the following:
+    ///  - The armed state: if sysfs reports the watchdog as inactive (e.g. after [`SetOptionFlags::DisableCard`]), 
+    ///    no reset can happen.
+    ///  - The 'nowayout' setting (see [`can_disable()`](Self::can_disable)): if set, the watchdog can't be stopped, 
+    ///    so a reset will happen. If it is not exposed by sysfs, it is assumed not set.
+    ///  - The magic close capability (see [`OptionFlags::MagicClose`]): drivers without it are stopped when the device is closed.
+    ///  - Whether [`magic_close()`](Self::magic_close) was called on this instance, and not cancelled by a later write
+    ///    (see [`KeepAliveMethod::Write`]).
+    ///
+    /// Note that a [`duplicate()`](Self::duplicate) keeps the watchdog open, so it must be released as well.
+    pub fn exit_would_reboot(&self) -> Result<bool, WatchdogError> {
+        if !self.is_running().unwrap_or(true){
+            return Ok(false);
         }
+        match self.can_disable(){
+            Ok(false) => return Ok(true),
+            Ok(true) => {},
+            Err(WatchdogError::Io(e)) if e.kind() == io::ErrorKind::Unsupported => trace!("nowayout not available:{}", e),
+            Err(e) => return Err(e),
+        }
+        if !self.is_option_supported(&OptionFlags::MagicClose)?{
+            return Ok(false);
+        }
+        Ok(!self.magic_closed)
+    }
+
+    /// Returns the pretimeout governors available to the watchdog.
+    ///
+    /// The governor decides what happens when the pretimeout expires (see [`get_pretimeout()`](Self::get_pretimeout)),
+    /// e.g. 'noop' only logs the event, while 'panic' triggers a kernel panic.
+    /// The list is read from sysfs ('pretimeout_available_governors'), whose format is parsed tolerantly:
+    /// the governors can be separated by newlines or spaces, and the active one can be enclosed in brackets, 
+    /// which are stripped. See [`active_pretimeout_governor()`](Self::active_pretimeout_governor) to know the active one.
+    ///
+    /// A [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`] is returned if the list is not exposed by sysfs
+    /// (e.g. the kernel was built without CONFIG_WATCHDOG_PRETIMEOUT_GOV).
+    pub fn available_pretimeout_governors(&self) -> Result<Vec<PretimeoutGovernor>, WatchdogError> {
+        sysfs::available_governors(&sysfs::sysfs_dir_for_file(&self.file)?)
+    }
+
+    /// Returns the number of pretimeout governors available to the watchdog.
+    ///
+    /// See [`available_pretimeout_governors()`](Self::available_pretimeout_governors).
+    pub fn pretimeout_governor_count(&self) -> Result<usize, WatchdogError> {
+        Ok(self.available_pretimeout_governors()?.len())
+    }
+
+    /// Returns the active pretimeout governor.
+    ///
+    /// If the list of the available governors marks the active one with brackets, that is returned.
+    /// Otherwise the governor is read from sysfs ('pretimeout_governor').
+    /// A [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`] is returned if neither is exposed by sysfs.
+    pub fn active_pretimeout_governor(&self) -> Result<PretimeoutGovernor, WatchdogError> {
+        sysfs::active_governor(&sysfs::sysfs_dir_for_file(&self.file)?)
+    }
+
+    /// Selects the pretimeout governor.
+    ///
+    /// The governor must be one of the [available ones](Self::available_pretimeout_governors), 
+    /// otherwise a [`WatchdogError::Io`] with [`io::ErrorKind::InvalidInput`] is returned.
+    /// The governor is written to sysfs ('pretimeout_governor'), which usually requires root privileges.
+    /// A [`WatchdogError::Io`] with [`io::ErrorKind::Unsupported`] is returned if the governors are not exposed by sysfs.
+    pub fn set_pretimeout_governor(&self, governor: &PretimeoutGovernor) -> Result<(), WatchdogError> {
+        sysfs::set_governor(&sysfs::sysfs_dir_for_file(&self.file)?, governor)
     }
 
     /// Disables the watchdog, if supported.
@@ -780,6 +2809,11 @@ impl Watchdog {
     /// So, if the watchdog daemon crashes, the system will reboot after the timeout has passed. 
     /// Watchdog devices also usually support the nowayout module parameter so that this option can be controlled at runtime.
     /// 
+    /// The instance stays usable after this, which is required when it is shared with the automatic keep alive thread 
+    /// (see [`start_automatic_keep_alive()`](Self::start_automatic_keep_alive)): any later write (e.g. [`keep_alive()`](Self::keep_alive)) 
+    /// cancels the magic close. When the watchdog is owned, prefer [`into_magic_close()`](Self::into_magic_close), 
+    /// which releases it right away and makes any later use a compile-time error.
+    /// 
     /// # Examples
     ///
     /// ```rust
@@ -803,22 +2837,464 @@ impl Watchdog {
     /// # Ok(())
     /// # }
     /// ```
-    pub fn magic_close(&mut self) -> std::io::Result<()>{
-        // If the automatic keepalive thread is running, send signal to close thread...
-        if self.msg_sender.is_some(){
-            // Drop sender, to let the receiver understand it must exit.
-            self.msg_sender = None;
-        }
+    pub fn magic_close(&mut self) -> Result<(), WatchdogError>{
+        self.stop_automatic_keep_alive();
 
         self.file.write_all(b"V")?;
         self.file.flush()?;
+        self.magic_closed = true;
         warn!("Magic close. The watchdog will NOT restart the system.");
         Ok(())
     }
+
+    /// Disables the watchdog, if supported, and releases it.
+    ///
+    /// This is the same as [`magic_close()`](Self::magic_close), but the instance is consumed and the file is closed right after
+    /// the magic character is written, so the magic close cannot be cancelled by mistake.
+    /// Note that any [`duplicate()`](Self::duplicate) keeps the watchdog open, so it must be released as well.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// use watchdog_device::{Watchdog, OptionFlags};
+    ///
+    /// # fn main() -> Result<(), std::io::Error> {
+    /// let mut wd = Watchdog::new()?;
+    /// wd.keep_alive()?;
+    /// if wd.is_option_supported(&OptionFlags::MagicClose)?{
+    ///     wd.into_magic_close()?;
+    /// }
+    /// # Ok(())
+    /// # }
+    /// ```
+    pub fn into_magic_close(mut self) -> Result<(), WatchdogError>{
+        // The explicit release prevails over the close behavior.
+        self.on_drop = OnDrop::LeaveToKernel;
+        self.magic_close()
+    }
+}
+
+/// Operations used to keep a watchdog alive.
+///
+/// This is implemented by [`Watchdog`], and allows writing code that is generic over the watchdog.
+/// The main purpose is testing: with the `test-util` feature, the crate provides 
+/// [`MockWatchdog`](crate::mock::MockWatchdog), which records the calls instead of using any hardware.
+/// See the documentation of the equivalent [`Watchdog`] methods for more information.
+pub trait KeepAlive{
+    /// Keeps the system alive. See [`Watchdog::keep_alive()`].
+    fn keep_alive(&mut self) -> Result<(), WatchdogError>;
+    /// Returns the configured timeout. See [`Watchdog::get_timeout()`].
+    fn get_timeout(&self) -> Result<i32, WatchdogError>;
+    /// Configures the timeout. See [`Watchdog::set_timeout()`].
+    fn set_timeout(&mut self, timeout: i32) -> Result<i32, WatchdogError>;
+    /// Disables the watchdog, if supported. See [`Watchdog::magic_close()`].
+    fn magic_close(&mut self) -> Result<(), WatchdogError>;
+}
+
+impl KeepAlive for Watchdog {
+    fn keep_alive(&mut self) -> Result<(), WatchdogError>{
+        Watchdog::keep_alive(self)
+    }
+
+    fn get_timeout(&self) -> Result<i32, WatchdogError>{
+        Watchdog::get_timeout(self)
+    }
+
+    fn set_timeout(&mut self, timeout: i32) -> Result<i32, WatchdogError>{
+        Watchdog::set_timeout(self, timeout)
+    }
+
+    fn magic_close(&mut self) -> Result<(), WatchdogError>{
+        Watchdog::magic_close(self)
+    }
+}
+
+/// Exposes the file descriptor of the device, e.g. to register it in an event loop.
+///
+/// The descriptor can be used for the ioctls and for polling, but writing to it directly is not supported, 
+/// except for the keep alive and magic close characters documented by the kernel: any other write bypasses 
+/// the bookkeeping of the instance (e.g. the magic close state, see [`Watchdog::exit_would_reboot()`]).
+/// The descriptor is owned by the instance, so it must not be closed.
+#[cfg(unix)]
+impl AsRawFd for Watchdog {
+    fn as_raw_fd(&self) -> RawFd{
+        self.file.as_raw_fd()
+    }
+}
+
+/// Borrows the file descriptor of the device. See the [`AsRawFd`] implementation for the caveats.
+#[cfg(unix)]
+impl AsFd for Watchdog {
+    fn as_fd(&self) -> BorrowedFd<'_>{
+        self.file.as_fd()
+    }
+}
+
+/// One-line summary of the watchdog: the device path, the driver identity, the timeout and the time left
+/// (e.g. "/dev/watchdog0 (Software Watchdog): timeout 60s, time left 58s").
+///
+/// Each value that can't be read is rendered as "unknown". See [`Watchdog::diagnostic_dump()`] for a complete report.
+impl fmt::Display for Watchdog {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let secs = |value: Result<i32, WatchdogError>| match value{
+            Ok(value) => format!("{}s", value),
+            Err(_) => String::from("unknown"),
+        };
+        write!(f, "{} ({}): timeout {}, time left {}", self.path.display(),
+               self.get_driver_identity().unwrap_or_else(|_| String::from("unknown")),
+               secs(self.get_timeout()), secs(self.get_time_left()))
+    }
+}
+
+/// Pings the watchdog from a loop protected by [`Watchdog::protect()`].
+pub struct KeepAliveTicker<'a>{
+    watchdog: &'a mut Watchdog,
+}
+
+impl KeepAliveTicker<'_> {
+    /// Keeps the system alive. See [`Watchdog::keep_alive()`].
+    pub fn tick(&mut self) -> Result<(), WatchdogError>{
+        self.watchdog.keep_alive()
+    }
+}
+
+/// Converts a number of seconds returned by the driver into a [`Duration`].
+fn secs_to_duration(secs: i32) -> Result<Duration, WatchdogError>{
+    match u64::try_from(secs){
+        Ok(secs) => Ok(Duration::from_secs(secs)),
+        Err(_) => Err(Errno::ERANGE.into()),
+    }
+}
+
+/// Converts a [`Duration`] into the whole number of seconds expected by the driver, rounding up.
+fn duration_to_secs(duration: Duration) -> Result<i32, WatchdogError>{
+    let secs = duration.as_secs().saturating_add(u64::from(duration.subsec_nanos() > 0));
+    i32::try_from(secs).map_err(|_| Errno::EINVAL.into())
+}
+
+/// Appends a line of [`Watchdog::diagnostic_dump()`].
+fn dump_line<T: fmt::Display, E: fmt::Display>(report: &mut String, label: &str, value: Result<T, E>){
+    report.push_str(&format!("{}: {}\n", label, dump_value(value)));
+}
+
+/// Formats a value of [`Watchdog::diagnostic_dump()`], marking it as unsupported in case of error.
+fn dump_value<T: fmt::Display, E: fmt::Display>(value: Result<T, E>) -> String{
+    match value{
+        Ok(value) => value.to_string(),
+        Err(e) => format!("unsupported ({})", e),
+    }
+}
+
+/// Pings the watchdog by writing the given bytes (any character other than 'V' pings it).
+///
+/// A write returning `Ok(0)` means that nothing was written, so the watchdog was not pinged: 
+/// this is reported as [`WatchdogError::ZeroWrite`], instead of being retried (as `write_all()` would) or taken as a success.
+fn write_keepalive<W: Write>(writer: &mut W, bytes: &[u8]) -> Result<(), WatchdogError>{
+    let mut remaining = bytes;
+    while !remaining.is_empty(){
+        match writer.write(remaining){
+            Ok(0) => return Err(WatchdogError::ZeroWrite),
+            Ok(written) => remaining = &remaining[written..],
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {},
+            Err(e) => return Err(e.into()),
+        }
+    }
+    writer.flush()?;
+    Ok(())
+}
+
+/// Converts the identity reported by the driver into a String, discarding the trailing NUL bytes.
+pub(crate) fn identity_to_string(identity: &[u8]) -> String {
+    let end = identity.iter().position(|&c| c == 0).unwrap_or(identity.len());
+    String::from_utf8_lossy(&identity[..end]).into_owned()
+}
+
+/// Sets the level of the messages logged when a watchdog is opened or closed.
+///
+/// These lifecycle events are logged at info level by default. Libraries doing their own logging 
+/// can lower the level, or silence them entirely with [`LevelFilter::Off`].
+/// The setting is global, and applies to the watchdogs opened or closed afterwards.
+/// Errors and the other messages are not affected.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::set_lifecycle_log_level;
+/// use log::LevelFilter;
+///
+/// set_lifecycle_log_level(LevelFilter::Off);
+/// ```
+pub fn set_lifecycle_log_level(level: LevelFilter){
+    LIFECYCLE_LOG_LEVEL.store(level as usize, Ordering::Relaxed);
+}
+
+/// Logs a lifecycle event, at the level set with [`set_lifecycle_log_level()`].
+fn log_lifecycle(args: fmt::Arguments){
+    let level = match LIFECYCLE_LOG_LEVEL.load(Ordering::Relaxed){
+        1 => Level::Error,
+        2 => Level::Warn,
+        3 => Level::Info,
+        4 => Level::Debug,
+        5 => Level::Trace,
+        _ => return,
+    };
+    log!(level, "{}", args);
+}
+
+/// Returns the watchdog devices present in the system.
+///
+/// '/dev' is scanned for the 'watchdog' and 'watchdogN' entries, which are returned sorted by numeric ID, 
+/// starting from the default one ('/dev/watchdog'), if present. 
+/// The devices are not opened, so none of them is activated: this allows a management daemon 
+/// to pick a device, or to iterate over all of them.
+/// If '/dev' does not exist, the list is empty.
+///
+/// # Examples
+///
+/// ```rust
+/// use watchdog_device::list_devices;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// for device in list_devices()?{
+///     println!("{}", device.display());
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn list_devices() -> Result<Vec<PathBuf>, WatchdogError>{
+    list_devices_in(Path::new("/dev"))
+}
+
+/// Returns the device node that the watchdog with the given ID is actually opened through.
+///
+/// On many systems '/dev/watchdog' (ID `None`) and '/dev/watchdog0' (ID `Some(0)`) are the same watchdog:
+/// either the former is a symlink to the latter, or it is the legacy misc node, which the kernel binds to the watchdog with ID 0.
+/// Opening both fails with EBUSY, so this allows telling whether two IDs refer to the same watchdog before opening them:
+/// symlinks are resolved, and the legacy node is resolved to '/dev/watchdog0'. Nothing is opened.
+///
+/// # Examples
+///
+/// ```rust,no_run
+/// use watchdog_device::resolve_device;
+///
+/// # fn main() -> Result<(), std::io::Error> {
+/// if resolve_device(None)? == resolve_device(Some(0))?{
+///     println!("'/dev/watchdog' is '/dev/watchdog0'");
+/// }
+/// # Ok(())
+/// # }
+/// ```
+pub fn resolve_device(id: Option<u8>) -> Result<PathBuf, WatchdogError>{
+    let path = match id{
+        Some(id) => PathBuf::from(format!("/dev/watchdog{}", id)),
+        None => PathBuf::from("/dev/watchdog"),
+    };
+    let real_path = fs::canonicalize(&path)?;
+    if sysfs::is_legacy_rdev(sysfs::rdev(&fs::metadata(&real_path)?)?){
+        let first = Path::new("/dev/watchdog0");
+        if first.exists(){
+            return Ok(fs::canonicalize(first)?);
+        }
+    }
+    Ok(real_path)
+}
+
+/// Returns the watchdog devices present in the given directory, as [`list_devices()`] does.
+fn list_devices_in(dir: &Path) -> Result<Vec<PathBuf>, WatchdogError>{
+    let entries = match fs::read_dir(dir){
+        Ok(entries) => entries,
+        Err(e) if e.kind() == io::ErrorKind::NotFound => return Ok(Vec::new()),
+        Err(e) => return Err(e.into()),
+    };
+    let mut devices: Vec<(Option<u32>, PathBuf)> = entries
+        .filter_map(|entry| entry.ok())
+        .filter_map(|entry| {
+            let name = entry.file_name().to_string_lossy().into_owned();
+            let suffix = name.strip_prefix("watchdog")?;
+            if suffix.is_empty(){
+                Some((None, entry.path()))
+            }
+            else{
+                suffix.parse::<u32>().ok().map(|id| (Some(id), entry.path()))
+            }
+        })
+        .collect();
+    devices.sort();
+    Ok(devices.into_iter().map(|(_, path)| path).collect())
 }
 
 impl Drop for Watchdog {
     fn drop(&mut self) {
-        warn!("Closing watchdog file...");
+        match self.on_drop{
+            OnDrop::LeaveToKernel => {},
+            OnDrop::MagicClose => {
+                if !self.magic_closed && self.is_option_supported(&OptionFlags::MagicClose).unwrap_or(false){
+                    if let Err(e) = self.magic_close(){
+                        error!("Magic close on drop failed:{}", e);
+                    }
+                }
+            },
+            OnDrop::KeepArmed => {
+                if self.magic_closed{
+                    match self.ping(KeepAliveMethod::Write){
+                        Ok(_) => info!("Magic close cancelled on drop."),
+                        Err(e) => error!("Couldn't cancel the magic close on drop:{}", e),
+                    }
+                }
+                if matches!(self.is_option_supported(&OptionFlags::MagicClose), Ok(false)){
+                    warn!("The driver doesn't support magic close, so it stops the watchdog on close.");
+                }
+            },
+        }
+        log_lifecycle(format_args!("Closing watchdog file..."));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Writer accepting a limited number of bytes, and then returning `Ok(0)`.
+    struct MockWriter{
+        capacity: usize,
+        written: Vec<u8>,
+    }
+
+    impl Write for MockWriter {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize>{
+            let len = buf.len().min(self.capacity - self.written.len()).min(1);
+            self.written.extend_from_slice(&buf[..len]);
+            Ok(len)
+        }
+
+        fn flush(&mut self) -> io::Result<()>{
+            Ok(())
+        }
+    }
+
+    #[test]
+    fn test_lifecycle_log_level() {
+        set_lifecycle_log_level(LevelFilter::Off);
+        assert_eq!(LIFECYCLE_LOG_LEVEL.load(Ordering::Relaxed), 0);
+        log_lifecycle(format_args!("Not logged."));
+        set_lifecycle_log_level(LevelFilter::Info);
+        assert_eq!(LIFECYCLE_LOG_LEVEL.load(Ordering::Relaxed), LevelFilter::Info as usize);
+    }
+
+    #[test]
+    fn test_list_devices_in() -> Result<(), WatchdogError> {
+        let dir = std::env::temp_dir().join(format!("watchdog-device-test-dev-{}", std::process::id()));
+        fs::create_dir(&dir)?;
+        for name in ["watchdog10", "watchdog", "watchdog2", "watchdogx", "null"]{
+            fs::write(dir.join(name), b"")?;
+        }
+        let devices = list_devices_in(&dir)?;
+        fs::remove_dir_all(&dir)?;
+        assert_eq!(devices, ["watchdog", "watchdog2", "watchdog10"].map(|name| dir.join(name)));
+        assert!(list_devices_in(&dir)?.is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn test_identity_to_string() {
+        // Same layout as 'watchdog_info.identity'.
+        let mut identity = [0u8; 32];
+        identity[..7].copy_from_slice(b"softdog");
+        assert_eq!(identity_to_string(&identity), "softdog");
+        // A full buffer has no NUL terminator.
+        assert_eq!(identity_to_string(&[b'a'; 32]).len(), 32);
+        assert_eq!(identity_to_string(&[0u8; 32]), "");
+    }
+
+    #[test]
+    fn test_write_keepalive_zero_write() {
+        let mut writer = MockWriter{capacity: 0, written: Vec::new()};
+        assert!(matches!(write_keepalive(&mut writer, b"1"), Err(WatchdogError::ZeroWrite)));
+        // A partial write followed by Ok(0) is not a success either.
+        let mut writer = MockWriter{capacity: 1, written: Vec::new()};
+        assert!(matches!(write_keepalive(&mut writer, b"12"), Err(WatchdogError::ZeroWrite)));
+        let mut writer = MockWriter{capacity: 2, written: Vec::new()};
+        assert!(write_keepalive(&mut writer, b"12").is_ok());
+        assert_eq!(writer.written, b"12");
+    }
+
+    #[test]
+    fn test_keep_alive_unregistered_ping() -> Result<(), WatchdogError> {
+        let path = std::env::temp_dir().join(format!("watchdog-device-test-unregistered-{}", std::process::id()));
+        fs::write(&path, b"")?;
+        let mut wd = Watchdog::open_path(&path)?;
+        let mut unregistered_ping_counter = MAX_UNREGISTERED_PINGS - 1;
+        // The ping reached the file, but it is not registered: it counts as failed, and the watchdog as not fed.
+        assert_eq!(wd.keep_alive_verified(&mut unregistered_ping_counter, |_| Ok(false)), Err(Errno::EIO));
+        assert_eq!(wd.ping_stats(), (0, 1));
+        assert_eq!(wd.time_since_last_keep_alive(), None);
+        assert_eq!(wd.keep_alive_verified(&mut unregistered_ping_counter, |_| Ok(true)), Ok(()));
+        assert_eq!(unregistered_ping_counter, 0);
+        // The first unregistered pings are tolerated.
+        assert_eq!(wd.keep_alive_verified(&mut unregistered_ping_counter, |_| Ok(false)), Ok(()));
+        assert_eq!(wd.ping_stats(), (2, 1));
+        assert!(wd.time_since_last_keep_alive().is_some());
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_alive_fallback_cached_on_success() -> Result<(), WatchdogError> {
+        // Neither the ioctl nor the write succeed on '/dev/full': the ioctl is tried again on the next ping.
+        let mut wd = Watchdog::open_path("/dev/full")?;
+        assert_eq!(wd.keep_alive_errno(), Err(Errno::ENOSPC));
+        assert_eq!(wd.keepalive_method, KeepAliveMethod::Ioctl);
+        let path = std::env::temp_dir().join(format!("watchdog-device-test-fallback-{}", std::process::id()));
+        wd.file = fs::OpenOptions::new().write(true).create(true).truncate(true).open(&path)?;
+        assert_eq!(wd.keep_alive_errno(), Ok(()));
+        assert_eq!(wd.keepalive_method, KeepAliveMethod::Write);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_auto_keep_alive_timeout_change() -> Result<(), WatchdogError> {
+        let path = std::env::temp_dir().join(format!("watchdog-device-test-auto-{}", std::process::id()));
+        fs::write(&path, b"")?;
+        let wd = Watchdog::open_path(&path)?;
+        // A regular file has no timeout: pretend that the driver reported one.
+        wd.cached_timeout.store(60, Ordering::Relaxed);
+        let wd = Arc::new(Mutex::new(wd));
+        let handle = Watchdog::start_automatic_keep_alive_auto(wd.clone());
+        thread::sleep(Duration::from_millis(100));
+        assert_eq!(wd.lock().unwrap().ping_stats(), (1, 0));
+        // As done by 'set_timeout()': the thread pings right away, and then once every second instead of 30.
+        {
+            let wd = wd.lock().unwrap();
+            wd.cached_timeout.store(2, Ordering::Relaxed);
+            wd.notify_timeout_change();
+        }
+        thread::sleep(Duration::from_millis(1500));
+        wd.lock().unwrap().stop_automatic_keep_alive();
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::Requested);
+        assert!(wd.lock().unwrap().ping_stats().0 >= 3);
+        fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_fixed_keep_alive_timeout_change() -> Result<(), WatchdogError> {
+        let path = std::env::temp_dir().join(format!("watchdog-device-test-fixed-{}", std::process::id()));
+        fs::write(&path, b"")?;
+        let wd = Arc::new(Mutex::new(Watchdog::open_path(&path)?));
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(wd.clone(), Duration::from_secs(30));
+        thread::sleep(Duration::from_millis(100));
+        // The interval is longer than the new timeout: the thread pings at half of it instead.
+        {
+            let wd = wd.lock().unwrap();
+            wd.cached_timeout.store(1, Ordering::Relaxed);
+            wd.notify_timeout_change();
+        }
+        thread::sleep(Duration::from_millis(1200));
+        wd.lock().unwrap().stop_automatic_keep_alive();
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::Requested);
+        assert!(wd.lock().unwrap().ping_stats().0 >= 4);
+        fs::remove_file(&path)?;
+        Ok(())
     }
 }
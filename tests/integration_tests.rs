@@ -1,6 +1,6 @@
 #[cfg(test)]
 mod tests {
-    use watchdog_device::{Watchdog, OptionFlags, SetOptionFlags};
+    use watchdog_device::{Watchdog, OptionFlags, OnDrop, SetOptionFlags, SetTimeoutOutcome, SharedWatchdog, WatchdogBuilder, WatchdogError, WatchdogMonitor};
     use log::{error, warn, info, trace};
     use std::time::Duration;
     use std::thread::sleep;
@@ -25,6 +25,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_probe_path() -> Result<(), std::io::Error> {
+        init_logger();
+        let info = Watchdog::probe_path("/dev/watchdog")?;
+        info!("identity:{} options:{} firmware version:{}", info.identity, info.options, info.firmware_version);
+        assert!(!info.identity.contains('\0'));
+        // The device has been released, so it can be opened again.
+        let mut wd = Watchdog::new()?;
+        assert_eq!(wd.get_info().unwrap(), info);
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_open_by_id() -> Result<(), std::io::Error> {
         init_logger();
@@ -46,6 +61,33 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_open_first_available() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::open_first_available()?;
+        info!("first available watchdog:{}", wd.path().display());
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_new_softdog() -> Result<(), std::io::Error> {
+        init_logger();
+        match Watchdog::new_softdog(){
+            Ok(mut wd) => {
+                assert!(wd.is_software_watchdog().unwrap());
+                if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                    wd.magic_close()?;
+                }
+            }
+            // Not a failure: the softdog module could simply not be loaded.
+            Err(e) => warn!("Could not open the software watchdog. error:{}", e),
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_keep_alive() -> Result<(), std::io::Error> {
         init_logger();
@@ -60,7 +102,7 @@ mod tests {
             result = wd.keep_alive();
             match result{
                 Ok(_) => info!("Keep alive #{} sent.", counter),
-                Err(e) => {
+                Err(ref e) => {
                     error!("Keep alive #{} failed with error:{}", counter, e);
                     break;
                 }
@@ -74,6 +116,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_protect() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let iterations = wd.protect(|ticker| {
+            let mut iterations = 0;
+            while iterations < 3{
+                ticker.tick()?;
+                iterations += 1;
+                sleep(Duration::from_secs(1));
+            }
+            Ok::<i32, std::io::Error>(iterations)
+        })?;
+        assert_eq!(iterations, 3);
+        Ok(())
+    }
+
     #[test]
     fn test_magic_close() -> Result<(), std::io::Error> {
         init_logger();
@@ -96,7 +155,7 @@ mod tests {
         let result = wd.get_firmware_version();
         match result{
             Ok(fw_ver) => info!("Firmware ver:{}", fw_ver),
-            Err(errno) => {
+            Err(ref errno) => {
                 error!("error:{}", errno);
             },
         }
@@ -111,26 +170,16 @@ mod tests {
     fn test_get_option_flags() -> Result<(), std::io::Error> {
         init_logger();
         let mut wd = Watchdog::new()?;
-        // all the enum variants:
-        let options = vec![
-            OptionFlags::Overheat,       
-            OptionFlags::FanFault,       
-            OptionFlags::Extern1,        
-            OptionFlags::Extern2,        
-            OptionFlags::PowerUnder,     
-            OptionFlags::CardReset,     
-            OptionFlags::PowerOver,      
-            OptionFlags::SetTimeout,     
-            OptionFlags::MagicClose,     
-            OptionFlags::PreTimeout,     
-            OptionFlags::AlarmOnly,      
-            OptionFlags::KeepalivePing
-        ];
+        let supported = wd.supported_options().unwrap();
+        info!("supported options:{}", supported);
         let mut test_error = false;
-        for option in options{
-            let result = wd.is_option_supported(&option);
+        for option in OptionFlags::all(){
+            let result = wd.is_option_supported(option);
             match result{
-                Ok(opt_res) => info!("option {}:{}", option, opt_res),
+                Ok(opt_res) => {
+                    info!("option {}:{}", option, opt_res);
+                    assert_eq!(supported.contains(*option), opt_res);
+                },
                 Err(errno) => {
                     error!("error:{}", errno);
                     test_error = true;
@@ -145,6 +194,21 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_capabilities_cached() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        assert!(wd.capabilities_cached());
+        let options = wd.supported_options().unwrap();
+        info!("supported options:{:?}", options);
+        wd.refresh_capabilities().unwrap();
+        assert_eq!(wd.supported_options().unwrap(), options);
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_get_driver_identity() -> Result<(), std::io::Error> {
         init_logger();
@@ -152,7 +216,7 @@ mod tests {
         let result = wd.get_driver_identity();
         match result{
             Ok(ref identity) => info!("driver identity:{}", identity),
-            Err(errno) => {
+            Err(ref errno) => {
                 error!("error:{}", errno);
             },
         }
@@ -163,6 +227,36 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_get_driver_identity_from_sysfs() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = WatchdogBuilder::new().identity_from_sysfs(true).build()?;
+        let identity = wd.get_driver_identity().unwrap();
+        info!("driver identity:{}", identity);
+        assert!(!identity.is_empty());
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_builder_timeout() -> Result<(), std::io::Error> {
+        init_logger();
+        let result = WatchdogBuilder::new().path("/dev/watchdog").timeout(Duration::from_secs(14)).build();
+        match result{
+            Ok(mut wd) => {
+                info!("timeout:{}", wd.get_timeout().unwrap());
+                if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                    wd.magic_close()?;
+                }
+            },
+            Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported => warn!("Cannot set the timeout:{}", e),
+            Err(e) => return Err(e.into()),
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_get_timeout() -> Result<(), std::io::Error> {
         init_logger();
@@ -170,7 +264,7 @@ mod tests {
         let result = wd.get_timeout();
         match result{
             Ok(timeout) => info!("timeout:{} secs", timeout),
-            Err(errno) => {
+            Err(ref errno) => {
                 error!("error. errno:{}", errno);
             },
         }
@@ -181,6 +275,239 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_is_healthy() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        match wd.is_healthy(){
+            Ok(healthy) => info!("healthy:{}", healthy),
+            // Not a failure: the device could simply not support GETSTATUS.
+            Err(errno) => warn!("health unknown. error:{}", errno),
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretimeout_governors() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        match wd.available_pretimeout_governors(){
+            Ok(governors) => {
+                info!("pretimeout governors:{:?}", governors);
+                assert_eq!(wd.pretimeout_governor_count()?, governors.len());
+                let active = wd.active_pretimeout_governor()?;
+                info!("active pretimeout governor:{}", active);
+                assert!(governors.contains(&active));
+            },
+            // Not a failure: the kernel could simply not support the pretimeout governors.
+            Err(e) => warn!("Pretimeout governors not available. error:{}", e),
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_time_left_estimate() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        wd.keep_alive().unwrap();
+        sleep(Duration::from_secs(2));
+        let estimate = wd.time_left_estimate().unwrap();
+        info!("time left estimate:{:?}", estimate);
+        assert!(estimate <= Duration::from_secs((timeout - 2) as u64));
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_timeout_strict() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        if wd.is_option_supported(&OptionFlags::SetTimeout)?{
+            let timeout = wd.get_timeout().unwrap();
+            // The current timeout is surely accepted as is.
+            wd.set_timeout_strict(timeout)?;
+            match wd.set_timeout_strict(timeout + 1){
+                Ok(_) => info!("timeout {} accepted", timeout + 1),
+                Err(WatchdogError::Clamped{requested, applied}) => {
+                    info!("timeout {} clamped to {}", requested, applied);
+                    assert_eq!(wd.get_timeout().unwrap(), timeout);
+                },
+                Err(e) => panic!("set_timeout_strict failed:{}", e),
+            }
+            wd.set_timeout(timeout).unwrap();
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_alive_guard() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let magic_close = wd.is_option_supported(&OptionFlags::MagicClose)?;
+        {
+            let guard = wd.keep_alive_guard(Duration::from_secs(1)).magic_close_on_drop(magic_close);
+            sleep(Duration::from_secs(2));
+            assert!(!guard.is_finished());
+            assert!(guard.lock().ping_stats().0 > 0);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_watchdog_monitor() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        // With a margin as long as the timeout, every ping is late.
+        let mut monitor = WatchdogMonitor::new(wd, Duration::from_secs(timeout as u64));
+        assert!(monitor.ping()?);
+        assert!(monitor.ping()?);
+        assert_eq!(monitor.late_pings(), 2);
+        let mut wd = monitor.into_inner();
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_timeout_checked() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        if wd.is_option_supported(&OptionFlags::SetTimeout)?{
+            let timeout = wd.get_timeout().unwrap();
+            // The current timeout is surely accepted as is.
+            assert_eq!(wd.set_timeout_checked(timeout)?, SetTimeoutOutcome::Exact(timeout));
+            let outcome = wd.set_timeout_checked(timeout + 1)?;
+            info!("timeout {}: {}", timeout + 1, outcome);
+            assert_eq!(wd.get_timeout().unwrap(), outcome.actual());
+            wd.set_timeout(timeout).unwrap();
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_exit_would_reboot() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            assert!(wd.exit_would_reboot()?);
+            wd.magic_close()?;
+            // Still true if nowayout is set.
+            assert_eq!(wd.exit_would_reboot()?, !wd.can_disable().unwrap_or(true));
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_diagnostic_dump() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let report = wd.diagnostic_dump();
+        info!("diagnostic dump:\n{}", report);
+        assert!(report.starts_with("Path: /dev/watchdog"));
+        // Only the status flags are reported with a status.
+        assert!(!report.contains(&format!("{}: status", OptionFlags::SetTimeout)));
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_boot_reason() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        wd.log_boot_reason();
+        if let Ok(reasons) = wd.boot_reason(){
+            for reason in reasons{
+                assert!(wd.get_boot_status(&reason).unwrap());
+            }
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_warn_margin() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        // Any ping after the first second is considered late, so a warning is logged.
+        wd.set_warn_margin(Duration::from_secs((timeout - 1) as u64));
+        sleep(Duration::from_secs(2));
+        wd.keep_alive().unwrap();
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_config_summary() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let summary = wd.config_summary();
+        info!("config summary:\n{}", summary);
+        assert!(summary.starts_with("let wd = Watchdog::new"));
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_get_timeout_duration() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        assert_eq!(wd.get_timeout_duration().unwrap(), Duration::from_secs(timeout as u64));
+        info!("watchdog:{}", wd);
+        assert!(wd.to_string().contains(&format!("timeout {}s", timeout)));
+        if let Ok(time_left) = wd.get_time_left_duration(){
+            assert!(time_left <= Duration::from_secs(timeout as u64));
+            assert!(wd.is_close_to_reset(Duration::from_secs(timeout as u64))?);
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_cached_timeout() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        assert_eq!(wd.cached_timeout(), Some(timeout));
+        if wd.is_option_supported(&OptionFlags::SetTimeout)?{
+            let returned_timeout = wd.set_timeout(timeout - 1)?;
+            assert_eq!(wd.cached_timeout(), Some(returned_timeout));
+            wd.set_timeout(timeout)?;
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_get_pretimeout() -> Result<(), std::io::Error> {
         init_logger();
@@ -224,8 +551,7 @@ mod tests {
                 Ok(time_left) => info!("time left:{} secs", time_left),
                 Err(errno) => {
                     error!("error. errno:{}", errno);
-                    assert!(false); // flag this test as failed.
-                    break;
+                    panic!("get_time_left failed"); // flag this test as failed.
                 },
             }
             sleep(Duration::from_secs(1));
@@ -236,13 +562,37 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_measure_effective_timeout() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        if wd.is_software_watchdog().unwrap(){
+            let timeout = wd.get_timeout().unwrap();
+            let effective_timeout = wd.measure_effective_timeout(Duration::from_secs(3))?;
+            info!("configured timeout:{} secs - effective timeout:{:?}", timeout, effective_timeout);
+            assert!(effective_timeout.as_secs().abs_diff(timeout as u64) <= 1);
+        }
+        else{
+            // Must refuse to run on real hardware.
+            assert!(wd.measure_effective_timeout(Duration::from_secs(3)).is_err());
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     #[test]
     fn test_get_temp() -> Result<(), std::io::Error> {
         init_logger();
         let mut wd = Watchdog::new()?;
         let result = wd.get_temp();
         match result{
-            Ok(temp) => info!("temperature:{}F", temp),
+            Ok(temp) => {
+                info!("temperature:{}F", temp);
+                let celsius = wd.get_temp_celsius().unwrap();
+                assert!((celsius - (temp as f32 - 32.0) * 5.0 / 9.0).abs() < 0.01);
+            },
             Err(errno) => {
                 // Do not let the test fail, since it is possible that the card simply doesn't support this feature.
                 // There is no other way of knowing if it is supported; 
@@ -271,19 +621,8 @@ mod tests {
     fn test_get_status() -> Result<(), std::io::Error> {
         init_logger();
         let mut wd = Watchdog::new()?;
-        // all the enum variants:
-        let options = vec![
-            OptionFlags::Overheat,       
-            OptionFlags::FanFault,       
-            OptionFlags::Extern1,        
-            OptionFlags::Extern2,        
-            OptionFlags::PowerUnder,     
-            OptionFlags::CardReset,     
-            OptionFlags::PowerOver,      
-            OptionFlags::KeepalivePing
-        ];
         let mut test_error = false;
-        for option in options{
+        for &option in OptionFlags::status_flags(){
             let result = wd.get_status(&option);
             match result{
                 Ok(status) => info!("status {}:{}", option, status),
@@ -294,6 +633,12 @@ mod tests {
                 },
             }
         }
+        if !test_error{
+            // A single read, consistent with the per-option queries (KeepalivePing changes with the pings).
+            for (option, status) in wd.all_statuses().unwrap().into_iter().filter(|(option, _)| *option != OptionFlags::KeepalivePing){
+                assert_eq!(status, wd.get_status(&option).unwrap());
+            }
+        }
         if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
             wd.magic_close()?;
         }
@@ -305,19 +650,8 @@ mod tests {
     fn test_get_boot_status() -> Result<(), std::io::Error> {
         init_logger();
         let mut wd = Watchdog::new()?;
-        // all the enum variants:
-        let options = vec![
-            OptionFlags::Overheat,       
-            OptionFlags::FanFault,       
-            OptionFlags::Extern1,        
-            OptionFlags::Extern2,        
-            OptionFlags::PowerUnder,     
-            OptionFlags::CardReset,     
-            OptionFlags::PowerOver,      
-            OptionFlags::KeepalivePing
-        ];
         let mut test_error = false;
-        for option in options{
+        for &option in OptionFlags::status_flags(){
             let result = wd.get_boot_status(&option);
             match result{
                 Ok(status) => info!("boot status {}:{}", option, status),
@@ -328,6 +662,12 @@ mod tests {
                 },
             }
         }
+        if !test_error{
+            // A single read, consistent with the per-option queries.
+            for (option, status) in wd.all_boot_statuses().unwrap(){
+                assert_eq!(status, wd.get_boot_status(&option).unwrap());
+            }
+        }
         if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
             wd.magic_close()?;
         }
@@ -335,6 +675,25 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_boot_status_latching() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let option = OptionFlags::CardReset;
+        // Some drivers clear the boot status once read: the cached value must not change.
+        let first_read = wd.get_boot_status(&option);
+        let second_read = wd.get_boot_status(&option);
+        match wd.refresh_boot_status(){
+            Ok(_) => info!("boot status {} after refresh:{}", option, wd.get_boot_status(&option).unwrap()),
+            Err(errno) => warn!("Couldn't refresh boot status. errno:{}", errno),
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        assert_eq!(first_read.map_err(|e| e.errno()), second_read.map_err(|e| e.errno()));
+        Ok(())
+    }
+
     #[test]
     fn test_set_timeout() -> Result<(), std::io::Error> {
         init_logger();
@@ -390,6 +749,40 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_set_timeout_min() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let mut test_error = false;
+        if wd.is_option_supported(&OptionFlags::SetTimeout)?{
+            let original_timeout_val = wd.get_timeout().unwrap();
+            match wd.set_timeout_min(){
+                Ok(min_timeout) => {
+                    info!("minimum timeout:{} secs", min_timeout);
+                    if min_timeout > original_timeout_val{
+                        test_error = true;
+                        error!("The minimum timeout:{} is greater than the original timeout:{}.", 
+                            min_timeout, original_timeout_val);
+                    }
+                },
+                Err(e) => {
+                    error!("error:{}", e);
+                    test_error = true;
+                },
+            }
+            // Restore original value
+            wd.set_timeout(original_timeout_val)?;
+        }
+        else{
+            warn!("Option {} is not supported", OptionFlags::SetTimeout);
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        assert!(!test_error);
+        Ok(())
+    }
+
     #[test]
     fn test_set_pretimeout() -> Result<(), std::io::Error> {
         init_logger();
@@ -445,6 +838,108 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_keep_alive_and_report() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let margin = wd.keep_alive_and_report()?;
+        info!("margin after keep alive:{}", margin);
+        assert!(margin <= wd.get_timeout()?);
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_on_drop_magic_close() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        wd.keep_alive()?;
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            // Released by the drop, instead of an explicit magic close.
+            wd.set_on_drop(OnDrop::MagicClose);
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_is_running() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        // Opening the device starts it.
+        assert!(wd.is_running()?);
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_pretimeout_too_long() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let timeout = wd.get_timeout()?;
+        let pretimeout = wd.get_pretimeout();
+        // Rejected before reaching the driver, so the pretimeout does not change.
+        let result = wd.set_pretimeout(timeout);
+        assert!(matches!(result, Err(WatchdogError::PretimeoutTooLong{pretimeout: p, timeout: t}) if p == timeout && t == timeout));
+        assert_eq!(wd.get_pretimeout().ok(), pretimeout.ok());
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_enable_temp_panic_unsupported() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        // Enabling the panic would alter the card configuration, so only the rejection is tested.
+        if !wd.is_option_supported(&OptionFlags::Overheat).unwrap(){
+            let result = wd.enable_temp_panic();
+            assert!(matches!(result, Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_wait_until_time_left() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        wd.keep_alive()?;
+        let timeout = wd.get_timeout()?;
+        match wd.wait_until_time_left(Duration::from_secs(timeout as u64 - 1)){
+            Ok(_) => assert!(wd.get_time_left()? < timeout),
+            Err(e) => warn!("Time left not supported. error:{}", e),
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_can_disable() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        match wd.can_disable(){
+            Ok(can_disable) => {
+                info!("can disable:{}", can_disable);
+                assert_eq!(wd.is_nowayout().unwrap(), !can_disable);
+            },
+            // Not a failure: sysfs could simply not expose the nowayout setting.
+            Err(e) => warn!("Couldn't tell if the watchdog can be disabled. error:{}", e),
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     // This test is ignored to avoid altering the current card configuration. 
     // It can of course be activated and tried manually, but it is ignored by default.
     #[ignore]
@@ -485,7 +980,7 @@ mod tests {
         init_logger();
         let wd = Watchdog::new()?;
         let result = wd.set_option(&SetOptionFlags::DisableCard);
-        match result{
+        match &result{
             Ok(_) => info!("Card disabled correctly."),
             Err(errno) => {
                 error!("error:{}", errno);
@@ -505,7 +1000,7 @@ mod tests {
         init_logger();
         let mut wd = Watchdog::new()?;
         let result = wd.set_option(&SetOptionFlags::EnableCard);
-        match result{
+        match &result{
             Ok(_) => info!("Card enabled correctly."),
             Err(errno) => {
                 error!("error:{}", errno);
@@ -542,6 +1037,153 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_automatic_keepalive_adaptive() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let wd_mutex_arc: Arc<Mutex<Watchdog>> = Arc::new(Mutex::new(wd));
+        let handle = Watchdog::start_automatic_keep_alive_adaptive(wd_mutex_arc.clone(), Duration::from_secs(1));
+
+        let mut wait_duration: u64 = 45; // By default the test will try to wait longer than a theoretical timeout delay.
+        if let Ok(timeout) = wd_mutex_arc.lock().expect("Mutex poisoned while getting timeout.").get_timeout(){
+            wait_duration = (timeout * 2) as u64;
+        }
+        info!("Sleeping for {} secs to verify that the watchdog won't restart the system...", wait_duration);
+        sleep(Duration::from_secs(wait_duration));
+
+        {
+            let locked_wd = &mut *wd_mutex_arc.lock().expect("Error obtaining lock guard.");
+            if locked_wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                locked_wd.magic_close()?;
+            }
+        }
+        handle.join().expect("Error joining thread.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_automatic_keepalive_auto() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let wd_mutex_arc: Arc<Mutex<Watchdog>> = Arc::new(Mutex::new(wd));
+        let handle = Watchdog::start_automatic_keep_alive_auto(wd_mutex_arc.clone());
+
+        let mut wait_duration: u64 = 45;
+        if let Ok(timeout) = wd_mutex_arc.lock().expect("Mutex poisoned while getting timeout.").get_timeout(){
+            wait_duration = (timeout * 2) as u64;
+        }
+        info!("Sleeping for {} secs to verify that the watchdog won't restart the system...", wait_duration);
+        sleep(Duration::from_secs(wait_duration));
+
+        {
+            let locked_wd = &mut *wd_mutex_arc.lock().expect("Error obtaining lock guard.");
+            if locked_wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                locked_wd.magic_close()?;
+            }
+        }
+        handle.join().expect("Error joining thread.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_automatic_keepalive_timeout_change() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        if !wd.is_option_supported(&OptionFlags::SetTimeout)?{
+            warn!("Option {} is not supported", OptionFlags::SetTimeout);
+            return Ok(());
+        }
+        let wd_mutex_arc: Arc<Mutex<Watchdog>> = Arc::new(Mutex::new(wd));
+        let handle = Watchdog::start_automatic_keep_alive_adaptive(wd_mutex_arc.clone(), Duration::from_secs(1));
+        sleep(Duration::from_secs(2));
+        // Shortening the timeout must not leave the thread waiting on the old interval.
+        let (old_timeout, new_timeout) = {
+            let locked_wd = wd_mutex_arc.lock().expect("Error obtaining lock guard.");
+            let old_timeout = locked_wd.get_timeout().unwrap();
+            (old_timeout, locked_wd.set_timeout(4).unwrap())
+        };
+        info!("Timeout changed from {} to {} secs. Sleeping for twice as long...", old_timeout, new_timeout);
+        sleep(Duration::from_secs((new_timeout * 2) as u64));
+
+        {
+            let locked_wd = &mut *wd_mutex_arc.lock().expect("Error obtaining lock guard.");
+            locked_wd.set_timeout(old_timeout).unwrap();
+            if locked_wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                locked_wd.magic_close()?;
+            }
+        }
+        handle.join().expect("Error joining thread.");
+        Ok(())
+    }
+
+    #[test]
+    fn test_keepalive_loop_in_thread() -> Result<(), std::io::Error> {
+        init_logger();
+        let wd = Watchdog::new()?;
+        let timeout = wd.get_timeout().unwrap();
+        let worker = Watchdog::keepalive_loop_in_thread(wd, Duration::from_secs(1));
+        info!("Sleeping for {} secs to verify that the watchdog won't restart the system...", timeout * 2);
+        sleep(Duration::from_secs((timeout * 2) as u64));
+        let stats = worker.stats();
+        info!("stats:{:?}", stats);
+        assert!(stats.keep_alives > 0);
+        assert_eq!(stats.errors, 0);
+        let mut wd = worker.stop();
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn test_status_reader() -> Result<(), std::io::Error> {
+        init_logger();
+        let shared = SharedWatchdog::new(Watchdog::new()?);
+        let handle = shared.start_automatic_keep_alive();
+        let reader = shared.status_reader()?;
+        let mut test_error = false;
+        for _ in 0..3{
+            // The reader must never need the lock held by the keep alive thread.
+            let _locked_wd = shared.lock()?;
+            match reader.get_timeout(){
+                Ok(timeout) => info!("timeout:{} secs", timeout),
+                Err(errno) => {
+                    error!("error. errno:{}", errno);
+                    test_error = true;
+                },
+            }
+        }
+        {
+            let locked_wd = &mut *shared.lock()?;
+            if locked_wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+                locked_wd.magic_close()?;
+            }
+        }
+        handle.join().expect("Error joining thread.");
+        assert!(!test_error);
+        Ok(())
+    }
+
+    #[test]
+    fn test_shared_shutdown() -> Result<(), std::io::Error> {
+        init_logger();
+        let shared = SharedWatchdog::new(Watchdog::new()?);
+        let handle = shared.start_automatic_keep_alive();
+        sleep(Duration::from_secs(2));
+        let extra_ref = shared.watchdog();
+        // Another reference is still alive, so the watchdog can't be unwrapped.
+        let shared = match shared.shutdown(Some(handle)){
+            Ok(_) => panic!("shutdown succeeded with a shared reference"),
+            Err(shared) => shared,
+        };
+        drop(extra_ref);
+        let mut wd = shared.shutdown(None).ok().expect("Watchdog still shared.");
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        Ok(())
+    }
+
     // This test is disabled because it is used to verify that the system actually resets 
     // when no magic_close is used before releasing the watchdog instance.
     #[ignore]
@@ -603,6 +1245,23 @@ mod tests {
         Ok(())
     }
 
+    #[test]
+    fn test_duplicate() -> Result<(), std::io::Error> {
+        init_logger();
+        let mut wd = Watchdog::new()?;
+        let mut wd_dup = wd.duplicate()?;
+        let handle = std::thread::spawn(move || wd_dup.keep_alive());
+        let result = handle.join().expect("Error joining thread.");
+        if let Err(ref e) = result{
+            error!("Keep alive on duplicate failed with error:{}", e);
+        }
+        if wd.is_option_supported(&OptionFlags::MagicClose).unwrap(){
+            wd.magic_close()?;
+        }
+        assert!(result.is_ok());
+        Ok(())
+    }
+
     #[test]
     fn test_successive_opening() -> Result<(), std::io::Error> {
         // Test opening and closign several times.
@@ -0,0 +1,533 @@
+// Unlike the integration tests, these tests do not need an actual watchdog in the system,
+// so they can safely be run on any Linux machine (e.g.: `cargo test --test offline_tests`).
+// They are Linux-only, since they rely on regular files, '/dev/full' and '/proc/self/fd' to stand in for a device.
+#[cfg(all(test, target_os = "linux"))]
+mod tests {
+    use watchdog_device::{explain_error, keep_alive_with_timer, KeepAlive, KeepAliveExit, KeepAliveMethod, MockWatchdog, OnDrop, OptionFlags, OptionSet, PretimeoutGovernor, resolve_device, SetOptionFlags, SysfsWatchdog, Watchdog, WatchdogBuilder, WatchdogError, WatchdogMonitor, WatchdogTimer};
+    use nix::errno::Errno;
+    use std::cell::Cell;
+    use std::collections::HashSet;
+    use std::future::Future;
+    use std::path::PathBuf;
+    use std::os::unix::io::{AsFd, AsRawFd, IntoRawFd};
+    use std::pin::pin;
+    use std::sync::{Arc, Mutex};
+    use std::task::{Context, Poll, Waker};
+    use std::time::Duration;
+
+    #[test]
+    fn test_explain_error() {
+        assert!(explain_error(Errno::ENOTTY).contains("ioctl"));
+        assert!(explain_error(Errno::EBUSY).contains("already in use"));
+        assert!(explain_error(Errno::EBUSY).contains("/dev/watchdog0"));
+        assert_eq!(explain_error(Errno::EACCES), explain_error(Errno::EPERM));
+        assert_eq!(explain_error(Errno::ECHILD), "unexpected error from the watchdog driver");
+    }
+
+    #[test]
+    fn test_mock_watchdog() -> Result<(), std::io::Error> {
+        let mut wd = MockWatchdog::new(60);
+        assert!(!wd.has_expired());
+        wd.keep_alive()?;
+        assert_eq!(wd.set_timeout(30)?, 30);
+        assert_eq!(wd.get_timeout()?, 30);
+        wd.fail_next_keep_alives(2, Errno::ENOTTY);
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOTTY));
+        wd.keep_alive()?;
+        wd.fail_next_set_timeout(Errno::EINVAL);
+        assert_eq!(wd.set_timeout(10).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.keep_alive_count(), 2);
+        assert_eq!(wd.set_timeout_calls(), &[30, 10]);
+        wd.expire();
+        assert!(wd.has_expired());
+        // Too late to prevent the reset.
+        wd.magic_close()?;
+        assert_eq!(wd.magic_close_count(), 1);
+        assert!(wd.has_expired());
+        wd.keep_alive()?;
+        assert!(wd.has_expired());
+        // The magic close prevents the expiration, until the next ping.
+        let mut wd = MockWatchdog::new(1);
+        wd.magic_close()?;
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(!wd.has_expired());
+        wd.keep_alive()?;
+        assert!(!wd.has_expired());
+        std::thread::sleep(Duration::from_millis(1100));
+        assert!(wd.has_expired());
+        Ok(())
+    }
+
+    /// Creates an empty temporary file, unique to the test and the process.
+    ///
+    /// A regular file does not support the watchdog ioctls (ENOTTY), while writing to it (e.g. the keep alive
+    /// fallback, or the magic close) succeeds, so the bytes written can be checked.
+    fn temp_file(name: &str) -> Result<PathBuf, std::io::Error> {
+        let path = std::env::temp_dir().join(format!("watchdog-device-test-{}-{}", name, std::process::id()));
+        std::fs::write(&path, b"")?;
+        Ok(path)
+    }
+
+    #[test]
+    fn test_open_path_regular_file() -> Result<(), std::io::Error> {
+        let path = temp_file("open")?;
+        let mut wd = Watchdog::open_path(&path)?;
+        assert_eq!(wd.path(), std::fs::canonicalize(&path)?);
+        assert_eq!(wd.as_fd().as_raw_fd(), wd.as_raw_fd());
+        assert_eq!(wd.time_since_last_keep_alive(), None);
+        assert_eq!(wd.to_string(), format!("{} (unknown): timeout unknown, time left unknown", wd.path().display()));
+        // A regular file has no sysfs directory, so the setting is unknown.
+        assert!(matches!(wd.is_nowayout(), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        // Without the sysfs state, an open device is assumed to be running.
+        assert!(wd.is_running()?);
+        wd.magic_close()?;
+        assert_eq!(std::fs::read(&path)?, b"V");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_timeout_validation() -> Result<(), std::io::Error> {
+        let path = temp_file("timeout")?;
+        let wd = Watchdog::open_path(&path)?;
+        assert_eq!(wd.get_timeout().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.set_timeout_duration(Duration::from_millis(1500)).unwrap_err().errno(), Some(Errno::ENOTTY));
+        // Out of range durations are rejected before reaching the device.
+        assert_eq!(wd.set_timeout_duration(Duration::from_secs(i32::MAX as u64 + 1)).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_pretimeout_duration(Duration::MAX).unwrap_err().errno(), Some(Errno::EINVAL));
+        // Invalid values are rejected as well, while the valid boundaries reach the device.
+        assert_eq!(wd.set_timeout(0).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_timeout(-1).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_timeout_duration(Duration::ZERO).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_timeout(1).unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.set_timeout_checked(0).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_timeout_checked(1).unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.set_pretimeout(-1).unwrap_err().errno(), Some(Errno::EINVAL));
+        assert_eq!(wd.set_pretimeout(0).unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.is_close_to_reset(Duration::from_secs(5)).unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.wait_until_time_left(Duration::from_secs(5)).unwrap_err().errno(), Some(Errno::ENOTTY));
+        wd.into_magic_close()?;
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_set_options_unsupported() -> Result<(), std::io::Error> {
+        let path = temp_file("options")?;
+        let wd = Watchdog::open_path(&path)?;
+        assert_eq!(wd.disable().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.enable().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.set_options(&[SetOptionFlags::EnableCard, SetOptionFlags::TempPanic]).unwrap_err().errno(), Some(Errno::ENOTTY));
+        // The capabilities can't be read, so the ioctl is not issued.
+        assert_eq!(wd.enable_temp_panic().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.all_statuses().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.all_boot_statuses().unwrap_err().errno(), Some(Errno::ENOTTY));
+        wd.into_magic_close()?;
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_try_clone() -> Result<(), std::io::Error> {
+        let path = temp_file("clone")?;
+        let mut wd = Watchdog::open_path(&path)?;
+        let mut wd_clone = wd.try_clone()?;
+        assert_eq!(wd_clone.path(), wd.path());
+        wd_clone.keep_alive().unwrap();
+        drop(wd_clone);
+        // The clone has its own bookkeeping.
+        assert!(wd.time_since_last_keep_alive().is_none());
+        assert_eq!(wd.ping_stats(), (0, 0));
+        wd.keep_alive().unwrap();
+        assert!(wd.time_since_last_keep_alive().unwrap() < Duration::from_secs(1));
+        assert_eq!(wd.ping_stats(), (1, 0));
+        wd.magic_close()?;
+        assert_eq!(std::fs::read(&path)?, b"11V");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_monitor_unknown_timeout() -> Result<(), std::io::Error> {
+        let path = temp_file("monitor")?;
+        // The timeout is unknown, so no ping is late.
+        let mut monitor = WatchdogMonitor::new(Watchdog::open_path(&path)?, Duration::from_secs(3600));
+        assert!(!monitor.ping()?);
+        assert_eq!(monitor.late_pings(), 0);
+        assert!(monitor.time_since_last_ping().is_some());
+        let mut wd = monitor.into_inner();
+        // The ping succeeds, but neither the time left nor the timeout can be read.
+        assert_eq!(wd.keep_alive_and_report().unwrap_err().errno(), Some(Errno::ENOTTY));
+        assert_eq!(wd.ping_stats(), (2, 0));
+        wd.magic_close()?;
+        assert_eq!(std::fs::read(&path)?, b"11V");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_explicit_ioctl_keepalive_method() -> Result<(), std::io::Error> {
+        let path = temp_file("ioctl")?;
+        let mut wd = WatchdogBuilder::new().path(&path).keepalive_method(KeepAliveMethod::Ioctl).build()?;
+        // The ioctl is explicitly chosen, so there is no fallback, but writing can still be requested.
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOTTY));
+        wd.keep_alive_via_write().unwrap();
+        assert_eq!(wd.ping_stats(), (1, 1));
+        wd.into_magic_close()?;
+        assert_eq!(std::fs::read(&path)?, b"1V");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_on_drop_keep_armed() -> Result<(), std::io::Error> {
+        let path = temp_file("drop")?;
+        let mut wd = Watchdog::open_path(&path)?;
+        wd.set_on_drop(OnDrop::KeepArmed);
+        wd.magic_close()?;
+        drop(wd);
+        // The magic close is cancelled on drop.
+        assert_eq!(std::fs::read(&path)?, b"V1");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_from_file() -> Result<(), std::io::Error> {
+        let path = temp_file("from-file")?;
+        let file = std::fs::OpenOptions::new().write(true).open(&path)?;
+        let mut wd = Watchdog::from_file(file);
+        assert_eq!(wd.path(), std::fs::canonicalize(&path)?);
+        wd.keep_alive().unwrap();
+        let fd = std::fs::OpenOptions::new().write(true).open(&path)?.into_raw_fd();
+        let mut wd_raw = unsafe { Watchdog::from_raw_fd(fd) };
+        wd_raw.keep_alive().unwrap();
+        drop(wd_raw);
+        wd.into_magic_close()?;
+        // Each descriptor has its own offset, so the second ping overwrites the first one.
+        assert_eq!(std::fs::read(&path)?, b"1V");
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_probe_and_build_regular_file() -> Result<(), std::io::Error> {
+        let path = temp_file("probe")?;
+        // The probe fails on the ioctl, and does not write anything.
+        assert_eq!(Watchdog::probe_path(&path).unwrap_err().raw_os_error(), Some(Errno::ENOTTY as i32));
+        assert_eq!(std::fs::read(&path)?, b"");
+        // Settings that cannot be applied make the builder fail and release the device.
+        let result = WatchdogBuilder::new().path(&path).timeout(Duration::from_secs(30)).build();
+        assert_eq!(result.err().and_then(|e| e.raw_os_error()), Some(Errno::ENOTTY as i32));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_alive_enotty_fallback() -> Result<(), std::io::Error> {
+        // A regular file fails the keep alive ioctl with ENOTTY, as the drivers that only support being written to.
+        let path = temp_file("enotty")?;
+        let mut wd = Watchdog::open_path(&path)?;
+        for _ in 0..3{
+            wd.keep_alive().unwrap();
+        }
+        assert_eq!(wd.ping_stats(), (3, 0));
+        drop(wd);
+        assert_eq!(std::fs::read(&path)?, b"111");
+        // If the write fails as well, the error of the write is returned.
+        let mut wd = Watchdog::open_path("/dev/full")?;
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOSPC));
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOSPC));
+        assert_eq!(wd.ping_stats(), (0, 2));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_automatic_keep_alive_regular_file() -> Result<(), std::io::Error> {
+        let path = temp_file("thread")?;
+        let wd = Arc::new(Mutex::new(Watchdog::open_path(&path)?));
+        // Stopping the thread leaves the watchdog usable.
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(wd.clone(), Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(50));
+        wd.lock().unwrap().stop_automatic_keep_alive();
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::Requested);
+        wd.lock().unwrap().stop_automatic_keep_alive();
+        wd.lock().unwrap().keep_alive().unwrap();
+
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(wd.clone(), Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(50));
+        wd.lock().unwrap().magic_close()?;
+        handle.join().unwrap();
+        // No ping follows the magic close.
+        let contents = std::fs::read(&path)?;
+        assert!(contents.len() > 2);
+        assert!(contents.ends_with(b"1V"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_keep_alive_guard_regular_file() -> Result<(), std::io::Error> {
+        let path = temp_file("guard")?;
+        {
+            let guard = Watchdog::open_path(&path)?.keep_alive_guard(Duration::from_millis(10));
+            std::thread::sleep(Duration::from_millis(50));
+            assert!(!guard.is_finished());
+            assert!(guard.lock().ping_stats().0 > 1);
+        }
+        // The thread is joined on drop, so no ping follows.
+        let contents = std::fs::read(&path)?;
+        assert!(contents.len() > 1);
+        std::thread::sleep(Duration::from_millis(30));
+        assert_eq!(std::fs::read(&path)?, contents);
+        // Stopping returns the watchdog, still usable.
+        std::fs::write(&path, b"")?;
+        let guard = Watchdog::open_path(&path)?.keep_alive_guard(Duration::from_millis(10));
+        let mut wd = guard.stop();
+        wd.keep_alive().unwrap();
+        wd.magic_close()?;
+        assert!(std::fs::read(&path)?.ends_with(b"1V"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_automatic_keep_alive_poisoned() -> Result<(), std::io::Error> {
+        let path = temp_file("poisoned")?;
+        let wd = Arc::new(Mutex::new(Watchdog::open_path(&path)?));
+        let wd_panic = wd.clone();
+        let _ = std::thread::spawn(move || {
+            let _guard = wd_panic.lock().unwrap();
+            panic!("Poisoning the mutex.");
+        }).join();
+        assert!(wd.is_poisoned());
+        // The thread keeps pinging anyway.
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(wd.clone(), Duration::from_millis(10));
+        std::thread::sleep(Duration::from_millis(50));
+        assert!(!handle.is_finished());
+        wd.lock().unwrap_or_else(|e| e.into_inner()).stop_automatic_keep_alive();
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::Requested);
+        assert!(std::fs::read(&path)?.len() > 1);
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[test]
+    fn test_automatic_keep_alive_max_errors() -> Result<(), std::io::Error> {
+        // Writing to '/dev/full' fails with ENOSPC, so every ping fails.
+        let mut wd = Watchdog::open_path("/dev/full")?;
+        assert_eq!(wd.keep_alive().unwrap_err().errno(), Some(Errno::ENOSPC));
+        assert_eq!(wd.time_since_last_keep_alive(), None);
+        assert_eq!(wd.ping_stats(), (0, 1));
+        assert_eq!(wd.keep_alive_via_write().unwrap_err().errno(), Some(Errno::ENOSPC));
+        assert_eq!(wd.ping_stats(), (0, 2));
+        let wd = Arc::new(Mutex::new(Watchdog::open_path("/dev/full")?));
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(wd.clone(), Duration::from_millis(1));
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::MaxErrors(10));
+        assert_eq!(wd.lock().unwrap().ping_stats(), (0, 10));
+
+        let wd = WatchdogBuilder::new().path("/dev/full").max_keepalive_errors(3).build()?;
+        let handle = Watchdog::start_automatic_keep_alive_with_interval(Arc::new(Mutex::new(wd)), Duration::from_millis(1));
+        assert_eq!(handle.join().unwrap(), KeepAliveExit::MaxErrors(3));
+        Ok(())
+    }
+
+    #[test]
+    fn test_errno_preservation() {
+        for errno in [Errno::EBADF, Errno::ENOTTY, Errno::EBUSY, Errno::EINVAL, Errno::EPERM, Errno::ENODEV]{
+            let raw = errno as i32;
+            // Errno -> WatchdogError -> io::Error
+            let e = WatchdogError::from(errno);
+            assert_eq!(e.raw_os_error(), Some(raw));
+            assert_eq!(std::io::Error::from(e).raw_os_error(), Some(raw));
+            // io::Error -> WatchdogError -> io::Error
+            let e = WatchdogError::from(std::io::Error::from_raw_os_error(raw));
+            assert_eq!(e.raw_os_error(), Some(raw));
+            assert_eq!(std::io::Error::from(e).raw_os_error(), Some(raw));
+            let e = WatchdogError::TimeoutLocked{requested: 10, errno};
+            assert_eq!(e.raw_os_error(), Some(raw));
+            assert_eq!(std::io::Error::from(e).raw_os_error(), Some(raw));
+            // MockWatchdog errors as well.
+            let mut wd = MockWatchdog::new(60);
+            wd.fail_next_keep_alives(1, errno);
+            assert_eq!(wd.keep_alive().unwrap_err().raw_os_error(), Some(raw));
+        }
+        assert_eq!(WatchdogError::Poisoned.raw_os_error(), None);
+    }
+
+    #[test]
+    fn test_keepalive_write_bytes_validation() {
+        // Rejected before the device is opened, so no watchdog is needed.
+        for bytes in [&b"V"[..], &b"1V"[..], &b""[..]]{
+            let result = WatchdogBuilder::new().keepalive_write_bytes(bytes).build();
+            assert!(matches!(result, Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::InvalidInput));
+        }
+    }
+
+    #[test]
+    fn test_watchdog_error_conversion() {
+        let e: std::io::Error = WatchdogError::from(Errno::EBUSY).into();
+        assert_eq!(e.raw_os_error(), Some(Errno::EBUSY as i32));
+        let e: std::io::Error = WatchdogError::Poisoned.into();
+        assert_eq!(e.kind(), std::io::ErrorKind::Other);
+        assert!(WatchdogError::from(Errno::ENOTTY).to_string().contains(explain_error(Errno::ENOTTY)));
+        // The common errno values are named, whatever their origin.
+        assert!(matches!(WatchdogError::from(Errno::EOPNOTSUPP), WatchdogError::NotSupported(Errno::EOPNOTSUPP)));
+        assert!(matches!(WatchdogError::from(Errno::EBUSY), WatchdogError::Busy));
+        assert!(matches!(WatchdogError::from(std::io::Error::from_raw_os_error(Errno::EBUSY as i32)), WatchdogError::Busy));
+        assert!(matches!(WatchdogError::from(std::io::Error::from_raw_os_error(Errno::EINVAL as i32)), WatchdogError::InvalidArgument));
+        assert!(matches!(WatchdogError::from(Errno::EIO), WatchdogError::Errno(Errno::EIO)));
+        // A custom error keeps its message.
+        let e = WatchdogError::from(std::io::Error::new(std::io::ErrorKind::InvalidInput, "custom message"));
+        assert!(matches!(&e, WatchdogError::Io(e) if e.kind() == std::io::ErrorKind::InvalidInput));
+        assert_eq!(e.to_string(), "custom message");
+        assert!(WatchdogError::Busy.to_string().contains("already in use"));
+        assert!(WatchdogError::TimeoutLocked{requested: 10, errno: Errno::EPERM}.to_string().contains("nowayout"));
+        let e = WatchdogError::PretimeoutTooLong{pretimeout: 30, timeout: 20};
+        assert!(e.to_string().contains("30") && e.to_string().contains("20"));
+        assert_eq!(std::io::Error::from(e).kind(), std::io::ErrorKind::InvalidInput);
+    }
+
+    /// Timer completing the first 'ready_sleeps' sleeps right away, and then never.
+    struct CountingTimer{
+        ready_sleeps: u32,
+        sleeps: Cell<u32>,
+    }
+
+    impl WatchdogTimer for CountingTimer {
+        fn sleep(&self, _duration: Duration) -> impl Future<Output = ()>{
+            let count = self.sleeps.get();
+            self.sleeps.set(count + 1);
+            let ready = count < self.ready_sleeps;
+            async move {
+                if !ready{
+                    std::future::pending::<()>().await;
+                }
+            }
+        }
+    }
+
+    #[test]
+    fn test_keep_alive_with_timer() {
+        let mut cx = Context::from_waker(Waker::noop());
+        let mut wd = MockWatchdog::new(60);
+        let timer = CountingTimer{ready_sleeps: 3, sleeps: Cell::new(0)};
+        {
+            let mut keep_alive = pin!(keep_alive_with_timer(&mut wd, &timer, Duration::from_secs(1)));
+            assert!(keep_alive.as_mut().poll(&mut cx).is_pending());
+        }
+        // One ping before each sleep.
+        assert_eq!(wd.keep_alive_count(), 4);
+        assert_eq!(timer.sleeps.get(), 4);
+
+        wd.fail_next_keep_alives(1, Errno::EIO);
+        let keep_alive = pin!(keep_alive_with_timer(&mut wd, &timer, Duration::from_secs(1)));
+        assert!(matches!(keep_alive.poll(&mut cx), Poll::Ready(Err(e)) if e.errno() == Some(Errno::EIO)));
+    }
+
+    #[test]
+    fn test_pretimeout_governor() {
+        assert_eq!("noop".parse::<PretimeoutGovernor>(), Ok(PretimeoutGovernor::Noop));
+        assert_eq!("panic\n".parse::<PretimeoutGovernor>(), Ok(PretimeoutGovernor::Panic));
+        assert_eq!("vendor".parse::<PretimeoutGovernor>(), Ok(PretimeoutGovernor::Other(String::from("vendor"))));
+        for governor in [PretimeoutGovernor::Noop, PretimeoutGovernor::Panic, PretimeoutGovernor::Other(String::from("vendor"))]{
+            assert_eq!(governor.to_string().parse::<PretimeoutGovernor>(), Ok(governor));
+        }
+    }
+    #[test]
+    fn test_option_set() {
+        let options = OptionSet::from_bits(0x8180);
+        assert!(options.contains(OptionFlags::SetTimeout));
+        assert!(!options.contains(OptionFlags::PreTimeout));
+        assert_eq!(options.iter().collect::<Vec<_>>(), OptionFlags::from_bitmask(0x8180));
+        assert_eq!(options.bits(), 0x8180);
+        // Unknown bits are kept, but not reported.
+        assert!(OptionSet::from_bits(0x4000).is_empty());
+        assert_eq!(OptionSet::from_bits(0x4000).unknown_bits(), 0x4000);
+        assert_eq!(options.unknown_bits(), 0);
+        assert_eq!(OptionSet::default().to_string(), "");
+        // The flags can be collected.
+        let flags: HashSet<OptionFlags> = options.iter().chain(options.iter()).collect();
+        assert_eq!(flags.len(), 3);
+        assert!(flags.contains(&OptionFlags::MagicClose));
+        let set_flags = HashSet::from([SetOptionFlags::DisableCard, SetOptionFlags::EnableCard, SetOptionFlags::DisableCard]);
+        assert_eq!(set_flags.len(), 2);
+    }
+
+    #[cfg(feature = "tokio")]
+    #[test]
+    fn test_spawn_keep_alive() -> Result<(), std::io::Error> {
+        let path = temp_file("tokio")?;
+        let runtime = tokio::runtime::Builder::new_current_thread().enable_time().build()?;
+        let mut wd = runtime.block_on(async {
+            let keep_alive = watchdog_device::spawn_keep_alive(Watchdog::open_path(&path)?, Duration::from_millis(10));
+            tokio::time::sleep(Duration::from_millis(50)).await;
+            assert!(!keep_alive.is_finished());
+            keep_alive.stop().await
+        })?;
+        wd.magic_close()?;
+        let contents = std::fs::read(&path)?;
+        assert!(contents.len() > 2);
+        assert!(contents.ends_with(b"1V"));
+        std::fs::remove_file(&path)?;
+        Ok(())
+    }
+
+    #[cfg(feature = "systemd")]
+    #[test]
+    fn test_systemd_watchdog_interval() {
+        // No other test reads these variables.
+        std::env::remove_var("WATCHDOG_PID");
+        std::env::set_var("WATCHDOG_USEC", "30000000");
+        assert_eq!(watchdog_device::systemd_watchdog_interval(), Some(Duration::from_secs(30)));
+        std::env::set_var("WATCHDOG_PID", (std::process::id() + 1).to_string());
+        assert_eq!(watchdog_device::systemd_watchdog_interval(), None);
+        std::env::remove_var("WATCHDOG_PID");
+        std::env::set_var("WATCHDOG_USEC", "0");
+        assert_eq!(watchdog_device::systemd_watchdog_interval(), None);
+        std::env::remove_var("WATCHDOG_USEC");
+        assert_eq!(watchdog_device::systemd_watchdog_interval(), None);
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_watchdog_info_serde() {
+        use watchdog_device::WatchdogInfo;
+        let info = WatchdogInfo{options: OptionSet::from_bits(0x8180), firmware_version: 1, identity: String::from("Software Watchdog")};
+        let json = serde_json::to_string(&info).unwrap();
+        assert_eq!(json, r#"{"options":["SetTimeout","MagicClose","KeepalivePing"],"firmware_version":1,"identity":"Software Watchdog"}"#);
+        assert_eq!(serde_json::from_str::<WatchdogInfo>(&json).unwrap(), info);
+    }
+
+    #[test]
+    fn test_option_flags_from_str() {
+        for option in OptionFlags::all(){
+            assert_eq!(option.to_string().parse(), Ok(*option));
+            assert_eq!(option.to_string().to_uppercase().parse(), Ok(*option));
+        }
+        assert_eq!(" PreTimeout\n".parse(), Ok(OptionFlags::PreTimeout));
+        assert!("Magic".parse::<OptionFlags>().unwrap_err().to_string().contains("'Magic'"));
+        assert_eq!(OptionFlags::all().len(), 12);
+        assert_eq!(OptionFlags::status_flags().len(), 8);
+        assert!(OptionFlags::status_flags().iter().all(|option| OptionFlags::all().contains(option)));
+    }
+
+    #[test]
+    fn test_sysfs_watchdog_missing() {
+        // No system has that many watchdogs.
+        let wd = SysfsWatchdog::new(255);
+        assert!(wd.dir().ends_with("watchdog255"));
+        assert!(matches!(wd.timeout(), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        assert!(matches!(wd.state(), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        assert!(matches!(wd.pretimeout_governor(), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        assert!(matches!(wd.available_pretimeout_governors(), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+        assert!(matches!(wd.set_pretimeout_governor(&PretimeoutGovernor::Panic), Err(WatchdogError::Io(e)) if e.kind() == std::io::ErrorKind::Unsupported));
+    }
+
+    #[test]
+    fn test_resolve_device_missing() {
+        assert_eq!(resolve_device(Some(255)).unwrap_err().errno(), Some(Errno::ENOENT));
+    }
+}